@@ -1,4 +1,4 @@
-use std::{fs::{read, File}, os::unix::prelude::AsRawFd};
+use std::{fs::{read, File}, os::unix::prelude::AsRawFd, time::Instant};
 
 use wayland_client::protocol::{
     wl_buffer, wl_compositor, wl_keyboard, wl_registry, wl_seat,
@@ -40,6 +40,9 @@ pub fn run(app: Application, assets: &str) {
         running: true,
         clicked: false,
         mouse: (0, 0),
+        wheel: (0.0, 0.0),
+        start: Instant::now(),
+        frame_requested: false,
     };
 
     println!("Starting the example window app, press <ESC> to quit.");
@@ -73,6 +76,12 @@ struct State {
     running: bool,
     clicked: bool,
     mouse: (usize, usize),
+    /// Wheel deltas accumulated since the last frame
+    wheel: (f64, f64),
+    /// Origin of the application's age
+    start: Instant,
+    /// Whether a `wl_callback` is pending
+    frame_requested: bool,
 }
 
 impl State {
@@ -88,6 +97,15 @@ impl State {
 
         self.xdg_surface = Some((xdg_surface, toplevel));
     }
+
+    /// Requests a frame, after user input for instance
+    fn request_frame(&mut self, qh: &QueueHandle<State>) {
+        if let (false, Some(surface)) = (self.frame_requested, &self.base_surface) {
+            surface.frame(qh, ());
+            surface.commit();
+            self.frame_requested = true;
+        }
+    }
 }
 
 impl Dispatch<wl_registry::WlRegistry, ()> for State {
@@ -140,6 +158,7 @@ impl Dispatch<wl_registry::WlRegistry, ()> for State {
                     if state.configured {
                         let surface = state.base_surface.as_ref().unwrap();
                         surface.frame(qh, ());
+                        state.frame_requested = true;
                         surface.attach(Some(&buffer), 0, 0);
                         surface.commit();
                     }
@@ -256,6 +275,7 @@ impl Dispatch<xdg_surface::XdgSurface, ()> for State {
             let surface = state.base_surface.as_ref().unwrap();
             if let Some(fb) = &state.fb {
                 surface.frame(qh, ());
+                state.frame_requested = true;
                 surface.attach(Some(&fb.buffer), 0, 0);
                 surface.commit();
             }
@@ -310,7 +330,11 @@ impl Dispatch<wl_callback::WlCallback, ()> for State {
         _: &Connection,
         qh: &QueueHandle<Self>,
     ) {
+        state.frame_requested = false;
+
         if let Some(fb) = &mut state.fb {
+            state.app.set_age(state.start.elapsed()).unwrap();
+
             while let Some(asset) = state.app.requested() {
                 println!("loading {}", asset);
                 let data = read(&format!("{}{}", &state.assets, asset)).unwrap();
@@ -332,6 +356,20 @@ impl Dispatch<wl_callback::WlCallback, ()> for State {
                 state.app.call_user_input_handler(node_key, &event).unwrap();
             }
 
+            // positive deltas move the content up / left
+            let (wheel_x, wheel_y) = std::mem::take(&mut state.wheel);
+            let node_key = state.app.get_implicit_focus();
+
+            if wheel_x != 0.0 {
+                let event = UserInputEvent::WheelX(SignedPixels::from_num(-wheel_x));
+                state.app.call_user_input_handler(node_key, &event).unwrap();
+            }
+
+            if wheel_y != 0.0 {
+                let event = UserInputEvent::WheelY(SignedPixels::from_num(-wheel_y));
+                state.app.call_user_input_handler(node_key, &event).unwrap();
+            }
+
             let now = std::time::Instant::now();
             let damages = state.app.render(size, fb.mapping.as_rgba_mut()).unwrap();
             let frame_ms = now.elapsed().as_millis();
@@ -341,7 +379,6 @@ impl Dispatch<wl_callback::WlCallback, ()> for State {
             state.clicked = false;
 
             let surface = state.base_surface.as_ref().unwrap();
-            surface.frame(qh, ());
             surface.attach(Some(&fb.buffer), 0, 0);
 
             for (position, size) in damages {
@@ -353,6 +390,12 @@ impl Dispatch<wl_callback::WlCallback, ()> for State {
                 surface.damage(x, y, w, h);
             }
 
+            // frames are rendered while animations, timers or asset requests are pending
+            if state.app.next_frame().is_some() {
+                surface.frame(qh, ());
+                state.frame_requested = true;
+            }
+
             surface.commit();
         }
     }
@@ -385,7 +428,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for State {
         event: wl_pointer::Event,
         _: &(),
         _: &Connection,
-        _: &QueueHandle<Self>,
+        qh: &QueueHandle<Self>,
     ) {
         match event {
             wl_pointer::Event::Motion { surface_x, surface_y, .. } => {
@@ -394,8 +437,15 @@ impl Dispatch<wl_pointer::WlPointer, ()> for State {
             wl_pointer::Event::Button { button: 272, state: WEnum::Value(wl_pointer::ButtonState::Pressed), .. } => {
                 state.clicked = true;
             },
-            _ => (),//println!("WlPointer: {:?}", event),
+            wl_pointer::Event::Axis { axis: WEnum::Value(axis), value, .. } => match axis {
+                wl_pointer::Axis::HorizontalScroll => state.wheel.0 += value,
+                wl_pointer::Axis::VerticalScroll => state.wheel.1 += value,
+                _ => return,
+            },
+            _ => return,//println!("WlPointer: {:?}", event),
         }
+
+        state.request_frame(qh);
     }
 }
 
@@ -427,4 +477,13 @@ macro_rules! app {
             $crate::run($crate::acrylic::core::app::Application::new($layout().into(), $callbacks), $path);
         }
     };
+    ($path:literal, $layout:expr, $callbacks:expr, $initial_state:expr, $libraries:expr) => {
+        fn main() {
+            let mut app = $crate::acrylic::core::app::Application::new($layout().into(), $callbacks);
+            for library in $libraries {
+                app.add_library(library).unwrap();
+            }
+            $crate::run(app, $path);
+        }
+    };
 }
@@ -1,11 +1,11 @@
 use acrylic::core::app::Application;
-use acrylic::core::{rgb::RGBA8, event::UserInputEvent, visual::{Position, SignedPixels}};
+use acrylic::core::{rgb::RGBA8, event::UserInputEvent, visual::{Position, SignedPixels, Direction}};
 
 pub use acrylic;
 
 use log::{error, set_logger, set_max_level, Record, LevelFilter, Level, Metadata};
 use std::fmt::Write;
-use core::str::from_utf8;
+use core::{str::from_utf8, time::Duration};
 
 extern "C" {
     fn raw_error(s: *const u8, l: usize);
@@ -19,6 +19,7 @@ extern "C" {
         framebuffer: *const u8,
     );
     fn raw_is_request_pending() -> usize;
+    fn raw_set_clipboard(s: *const u8, l: usize);
 }
 
 struct ConsoleLog;
@@ -107,11 +108,19 @@ pub extern "C" fn set_output_size(w: usize, h: usize) {
     };
 }
 
+/// Returns the delay until the next frame, in milliseconds,
+/// or -1 if no frame is needed until the next user input.
 #[export_name = "frame"]
-pub extern "C" fn frame(app: &mut Application, _age_ms: usize) {
+pub extern "C" fn frame(app: &mut Application, age_ms: usize) -> isize {
     let (fb_size, fb, _scratch) = unsafe { (FB_SIZE, &mut MAIN_FB, &mut SCRATCH) };
+    app.set_age(Duration::from_millis(age_ms as u64)).unwrap();
     app.render(fb_size, fb.as_mut().unwrap()).unwrap();
     ensure_pending_request(app);
+
+    match app.next_frame() {
+        Some(delay) => delay.as_millis() as isize,
+        None => -1,
+    }
 }
 
 pub static mut TEXT_INPUT: [u8; 16] = [0; 16];
@@ -144,17 +153,43 @@ pub extern "C" fn send_text_delete(app: &mut Application, delete: isize) {
         app.call_user_input_handler(node_key, &event).unwrap();
     }
 }
-/*
+
+#[export_name = "send_activation"]
+pub extern "C" fn send_activation(app: &mut Application) {
+    let event = UserInputEvent::Activate;
+    if let Some(node_key) = app.get_explicit_focus() {
+        app.call_user_input_handler(node_key, &event).unwrap();
+    }
+}
+/// Copies the text of the focused node (or of the hovered one) to the clipboard
+#[export_name = "send_text_copy"]
+pub extern "C" fn send_text_copy(app: &mut Application) {
+    let node_key = match app.get_explicit_focus() {
+        Some(node_key) => node_key,
+        None => app.get_implicit_focus(),
+    };
+
+    app.call_user_input_handler(node_key, &UserInputEvent::TextCopy).unwrap();
+
+    if let Some(text) = app.take_clipboard() {
+        unsafe { raw_set_clipboard(text.as_ptr(), text.len()) };
+    }
+}
+
 #[export_name = "send_dir_input"]
-pub extern "C" fn send_dir_input(_app: &mut Application, _dir: usize) {
+pub extern "C" fn send_dir_input(app: &mut Application, dir: usize) {
     let direction = [
         Direction::Up,
         Direction::Left,
         Direction::Down,
         Direction::Right,
     ][dir];
-    let _ = app.fire_event(&Event::DirInput(direction));
-}*/
+
+    let event = UserInputEvent::DirInput(direction);
+    if let Some(node_key) = app.get_explicit_focus() {
+        app.call_user_input_handler(node_key, &event).unwrap();
+    }
+}
 
 #[export_name = "mouse_move"]
 pub extern "C" fn mouse_move(app: &mut Application, x: usize, y: usize) {
@@ -162,6 +197,44 @@ pub extern "C" fn mouse_move(app: &mut Application, x: usize, y: usize) {
     app.set_focus_coords(Position::new(x, y)).unwrap();
 }
 
+#[export_name = "pointer_drag"]
+pub extern "C" fn pointer_drag(app: &mut Application, x: usize, y: usize) {
+    let (x, y) = (SignedPixels::from_num(x), SignedPixels::from_num(y));
+    app.set_focus_coords(Position::new(x, y)).unwrap();
+
+    let node_key = match app.get_explicit_focus() {
+        Some(node_key) => node_key,
+        None => app.get_implicit_focus(),
+    };
+
+    app.call_user_input_handler(node_key, &UserInputEvent::Drag).unwrap();
+}
+
+#[export_name = "pointer_drag_end"]
+pub extern "C" fn pointer_drag_end(app: &mut Application) {
+    let node_key = match app.get_explicit_focus() {
+        Some(node_key) => node_key,
+        None => app.get_implicit_focus(),
+    };
+
+    app.call_user_input_handler(node_key, &UserInputEvent::DragEnd).unwrap();
+}
+
+#[export_name = "wheel"]
+pub extern "C" fn wheel(app: &mut Application, dx: isize, dy: isize) {
+    let node_key = app.get_implicit_focus();
+
+    if dx != 0 {
+        let input_event = UserInputEvent::WheelX(SignedPixels::from_num(dx));
+        app.call_user_input_handler(node_key, &input_event).unwrap();
+    }
+
+    if dy != 0 {
+        let input_event = UserInputEvent::WheelY(SignedPixels::from_num(dy));
+        app.call_user_input_handler(node_key, &input_event).unwrap();
+    }
+}
+
 #[export_name = "quick_action"]
 pub extern "C" fn quick_action(app: &mut Application, action: usize, x: usize, y: usize) {
     let input_event = match action {
@@ -207,4 +280,15 @@ macro_rules! app {
             platform::wasm_init($path, app)
         }
     };
+    ($path:literal, $layout:expr, $callbacks:expr, $initial_state:expr, $libraries:expr) => {
+        #[export_name = "init"]
+        pub extern "C" fn init() -> &'static $crate::acrylic::core::app::Application {
+            platform::pre_init();
+            let mut app = $crate::acrylic::core::app::Application::new($layout().into(), $callbacks);
+            for library in $libraries {
+                app.add_library(library).unwrap();
+            }
+            platform::wasm_init($path, app)
+        }
+    };
 }
@@ -1,6 +1,6 @@
 use railway::{*, computing::{*, Operation::*}};
 use std::{fs, path::Path, env};
-use core::f32::consts::FRAC_PI_2;
+use core::f32::consts::{FRAC_PI_2, PI};
 
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
@@ -8,6 +8,9 @@ fn main() {
 
     let cont_rwy_dst = Path::new(&out_dir).join("container.rwy");
     fs::write(cont_rwy_dst, &gen_container_rwy()).unwrap();
+
+    let slider_rwy_dst = Path::new(&out_dir).join("slider.rwy");
+    fs::write(slider_rwy_dst, gen_slider_rwy()).unwrap();
 }
 
 fn gen_container_rwy() -> Vec<u8> {
@@ -224,3 +227,150 @@ fn gen_container_rwy() -> Vec<u8> {
 
     serialize(&arguments, &instructions, &[], &rendering_steps)
 }
+
+fn gen_slider_rwy() -> Vec<u8> {
+    let mut arguments = Vec::new();
+
+    let zero = arguments.len();
+    arguments.push(Argument::unnamed(C_ZERO));
+
+    let top_left = zero;
+
+    let size = arguments.len();
+    arguments.push(Argument::named("size", Couple::new(400.0, 40.0)));
+
+    let bottom_right = size;
+
+    // centers of the track caps
+    let track_start = arguments.len();
+    arguments.push(Argument::named("track-start", Couple::new(20.0, 20.0)));
+    let track_end = arguments.len();
+    arguments.push(Argument::named("track-end", Couple::new(380.0, 20.0)));
+
+    // center of the thumb
+    let thumb = arguments.len();
+    arguments.push(Argument::named("thumb", Couple::new(200.0, 20.0)));
+
+    // (track radius, thumb radius)
+    let radii = arguments.len();
+    arguments.push(Argument::named("radii", Couple::new(6.0, 18.0)));
+
+    let track_rg = arguments.len();
+    arguments.push(Argument::named("track-rg", Couple::new(0.4, 0.4)));
+    let track_ba = arguments.len();
+    arguments.push(Argument::named("track-ba", Couple::new(0.4, 1.0)));
+
+    let fill_rg = arguments.len();
+    arguments.push(Argument::named("fill-rg", Couple::new(0.3, 0.7)));
+    let fill_ba = arguments.len();
+    arguments.push(Argument::named("fill-ba", Couple::new(0.3, 1.0)));
+
+    let thumb_rg = arguments.len();
+    arguments.push(Argument::named("thumb-rg", Couple::new(1.0, 1.0)));
+    let thumb_ba = arguments.len();
+    arguments.push(Argument::named("thumb-ba", Couple::new(1.0, 1.0)));
+
+    let half_turn = arguments.len();
+    arguments.push(Argument::unnamed(Couple::new(PI, 0.0)));
+
+    let full_turn = arguments.len();
+    arguments.push(Argument::unnamed(Couple::new(2.0 * PI, 0.0)));
+
+    let mut instructions = Vec::new();
+
+    let track_radius = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(EachX2, radii, radii, 0));
+
+    let thumb_radius = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(EachY2, radii, radii, 0));
+
+    let v_offset = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Select2, zero, track_radius, 0));
+
+    let h_offset = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Select2, thumb_radius, zero, 0));
+
+    let track_nw = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Subtract2, track_start, v_offset, 0));
+
+    let track_sw = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Add2, track_start, v_offset, 0));
+
+    let track_ne = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Subtract2, track_end, v_offset, 0));
+
+    let track_se = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Add2, track_end, v_offset, 0));
+
+    let fill_ne = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Subtract2, thumb, v_offset, 0));
+
+    let fill_se = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Add2, thumb, v_offset, 0));
+
+    let thumb_east = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Add2, thumb, h_offset, 0));
+
+    let bottom_left = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Select2, top_left, bottom_right, 0));
+
+    let top_right = arguments.len() + instructions.len();
+    instructions.push(Instruction::new(Select2, bottom_right, top_left, 0));
+
+    let background = |color| vec![
+        Triangle {
+            points: [top_left, bottom_left, bottom_right],
+            colors: [color, color, color],
+        },
+        Triangle {
+            points: [top_left, top_right, bottom_right],
+            colors: [color, color, color],
+        },
+    ];
+
+    let track_background = background([track_rg, track_ba]);
+    let fill_background = background([fill_rg, fill_ba]);
+    let thumb_background = background([thumb_rg, thumb_ba]);
+
+    // a pill from the left cap to the right one
+    let pill = |end, end_ne, end_se| vec![
+        PathStep::Arc(Arc {
+            start_point: track_nw,
+            center: track_start,
+            deltas: half_turn,
+        }),
+        PathStep::Line(Line {
+            points: [track_sw, end_se],
+        }),
+        PathStep::Arc(Arc {
+            start_point: end_se,
+            center: end,
+            deltas: half_turn,
+        }),
+        PathStep::Line(Line {
+            points: [end_ne, track_nw],
+        }),
+    ];
+
+    let track_path = pill(track_end, track_ne, track_se);
+    let fill_path = pill(thumb, fill_ne, fill_se);
+
+    let thumb_path = vec![
+        PathStep::Arc(Arc {
+            start_point: thumb_east,
+            center: thumb,
+            deltas: full_turn,
+        }),
+        PathStep::Line(Line {
+            points: [thumb_east, thumb_east],
+        }),
+    ];
+
+    let rendering_steps: [RenderingStep<&Vec<PathStep>, &Vec<Triangle>>; 3] = [
+        RenderingStep::Clip(&track_path, &track_background),
+        RenderingStep::Clip(&fill_path, &fill_background),
+        RenderingStep::Clip(&thumb_path, &thumb_background),
+    ];
+
+    serialize(&arguments, &instructions, &[], &rendering_steps)
+}
@@ -0,0 +1,251 @@
+use crate::core::app::Application;
+use crate::core::event::{Handlers, DEFAULT_HANDLERS, UserInputEvent};
+use crate::core::node::{NodeKey, Mutator, MutatorIndex, get_storage};
+use crate::core::clock::TimerId;
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
+use crate::core::visual::{Pixels, Margin, Axis, LayoutMode, PixelSource, MAX_CONTENT_GAP};
+use crate::core::for_each_child;
+use crate::{FALSE_STR, ZERO_ARCSTR, Error, error, Box, ArcStr, ro_string};
+use super::container::Decoration;
+use core::{mem::ManuallyDrop, time::Duration};
+use oakwood::NodeKey as _;
+
+const ON_QUICK_ACTION: usize = 0;
+const DISABLED:        usize = 1;
+const STYLE:           usize = 2;
+const HOVER:           usize = 3;
+const PRESSED:         usize = 4;
+const DISABLED_STYLE:  usize = 5;
+const MARGIN:          usize = 6;
+const BORDER_WIDTH:    usize = 7;
+const BORDER_RADIUS:   usize = 8;
+const GAP:             usize = 9;
+const LENGTH:          usize = 10;
+
+const NEUTRAL_INERT: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("neutral-inert"));
+const NEUTRAL_FOCUS: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("neutral-focus"));
+const INCITE_FOCUS: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("incite-focus"));
+const DEFAULT: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("default"));
+
+/// Width of the focus ring, added to the border
+const FOCUS_RING: Pixels = Pixels::lit("2");
+
+/// How long the `pressed` style is displayed after an activation
+const PRESSED_DURATION: Duration = Duration::from_millis(150);
+
+pub const BUTTON_MUTATOR: Mutator = Mutator {
+    name: ro_string!("ButtonMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("button"),
+        attr_set: &[
+            ("on-quick-action", AttributeValueType::OptOther, None),
+            ("disabled", AttributeValueType::Boolean, Some(FALSE_STR)),
+            ("style", AttributeValueType::Other, Some(NEUTRAL_INERT)),
+            ("hover", AttributeValueType::Other, Some(NEUTRAL_FOCUS)),
+            ("pressed", AttributeValueType::Other, Some(INCITE_FOCUS)),
+            ("disabled-style", AttributeValueType::Other, Some(DEFAULT)),
+            ("margin", AttributeValueType::Pixels, Some(ZERO_ARCSTR)),
+            ("border-width", AttributeValueType::OptPixels, None),
+            ("border-radius", AttributeValueType::Pixels, Some(ZERO_ARCSTR)),
+            ("gap", AttributeValueType::Pixels, Some(ZERO_ARCSTR)),
+            ("length", AttributeValueType::OptPixels, None),
+            ("tooltip", AttributeValueType::OptOther, None),
+        ],
+        accepts_children: true,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        initializer,
+        populator,
+        resizer,
+        user_input_handler,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+struct ButtonStorage {
+    decoration: Decoration,
+    /// The last activated button, which is displayed with the
+    /// `pressed` style until its timer expires.
+    pressed: Option<(NodeKey, TimerId)>,
+}
+
+fn initializer(app: &mut Application, m: MutatorIndex) -> Result<(), Error> {
+    let storage = &mut app.mutators[usize::from(m)].storage;
+    assert!(storage.is_none());
+
+    *storage = Some(Box::new(ButtonStorage {
+        decoration: Decoration::new(),
+        pressed: None,
+    }));
+
+    Ok(())
+}
+
+fn is_disabled(app: &mut Application, node_key: NodeKey) -> Result<bool, Error> {
+    app.attr(node_key, DISABLED)
+}
+
+fn resolve_style(app: &mut Application, node_key: NodeKey, attr: usize) -> Result<usize, Error> {
+    let style: ArcStr = app.attr(node_key, attr)?;
+    app.theme.resolve(&style).ok_or_else(|| error!("{}: Invalid style name: {}", app.node_location(node_key), style))
+}
+
+fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    let qa_callback: Option<ArcStr> = app.attr(node_key, ON_QUICK_ACTION)?;
+    let content_gap: Pixels         = app.attr(node_key,             GAP)?;
+    let margin_attr: Pixels         = app.attr(node_key,          MARGIN)?;
+    let radius_attr: Pixels         = app.attr(node_key,   BORDER_RADIUS)?;
+    let      length: Option<Pixels> = app.attr(node_key,          LENGTH)?;
+
+    if let Some(qa_callback) = qa_callback {
+        if !app.callbacks.contains_key(&qa_callback) {
+            return Err(error!("{}: Unknown callback: {}", app.xml_location(xml_node_key), qa_callback));
+        }
+    }
+
+    if content_gap > MAX_CONTENT_GAP {
+        return Err(error!("{}: Gap is too large: {} (max: {})", app.xml_location(xml_node_key), content_gap, MAX_CONTENT_GAP));
+    }
+
+    // checks style names early
+    for attr in [STYLE, HOVER, PRESSED, DISABLED_STYLE] {
+        resolve_style(app, node_key, attr)?;
+    }
+
+    let layout_mode = match length {
+        Some(length) => LayoutMode::Fixed(length),
+        None => LayoutMode::WrapContent,
+    };
+
+    app.view[node_key].margin = Margin::quad(margin_attr + radius_attr);
+    app.view[node_key].config.set_content_axis(Axis::Horizontal);
+    app.view[node_key].config.set_content_gap(content_gap);
+    app.view[node_key].config.set_layout_mode(layout_mode);
+    app.view[node_key].config.set_hover_sensitivity(true);
+    app.invalidate_layout();
+
+    for_each_child!(app.xml_tree, xml_node_key, xml_child, {
+        let child_node = app.view.create();
+        app.view.append_children(child_node, node_key);
+        app.view[child_node].xml_node_index = Some(xml_child.index()).into();
+        app.view[child_node].factory = app.xml_tree[xml_child].factory;
+
+        app.call_populator(child_node, xml_child)?;
+    });
+
+    Ok(())
+}
+
+fn resizer(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    let border_width: Option<Pixels> = app.attr(node_key, BORDER_WIDTH)?;
+    let hovered = app.is_hovered(node_key);
+    let focused = app.get_explicit_focus() == Some(node_key);
+
+    let storage: &mut ButtonStorage = get_storage(&mut app.mutators, m).unwrap();
+    let pressed = storage.pressed.map(|(pressed, _)| pressed) == Some(node_key);
+
+    let style_attr = match (is_disabled(app, node_key)?, pressed, hovered) {
+        (true, _, _) => DISABLED_STYLE,
+        (false, true, _) => PRESSED,
+        (false, false, true) => HOVER,
+        (false, false, false) => STYLE,
+    };
+
+    let style_index = resolve_style(app, node_key, style_attr)?;
+    let style = app.theme.get(style_index);
+
+    let old_style = app.view[node_key].style_override.get();
+    app.view[node_key].style_override = Some(style_index.into()).into();
+    app.view[node_key].background = PixelSource::SolidColor(style.background);
+    app.view[node_key].config.set_dirty(true);
+
+    if !app.debug.skip_container_decoration {
+        let margin: Pixels = app.attr(node_key,        MARGIN)?;
+        let radius: Pixels = app.attr(node_key, BORDER_RADIUS)?;
+        let ext = app.get_inherited_style(node_key)?.background;
+        let size = app.view[node_key].size;
+
+        let border_width = match focused {
+            true => Some(border_width.unwrap_or(Pixels::ZERO) + FOCUS_RING),
+            false => border_width,
+        };
+
+        let storage: &mut ButtonStorage = get_storage(&mut app.mutators, m).unwrap();
+        let foreground = storage.decoration.render(size, margin, radius, border_width, style.outline, ext);
+        app.view[node_key].foreground = foreground;
+    }
+
+    // children (labels) inherit the style of the button
+    if old_style != Some(style_index.into()) {
+        for_each_child!(app.view, node_key, child, {
+            app.call_resizer(child)?;
+        });
+    }
+
+    Ok(())
+}
+
+fn user_input_handler(
+    app: &mut Application,
+    m: MutatorIndex,
+    node_key: NodeKey,
+    _target: NodeKey,
+    event: &UserInputEvent,
+) -> Result<bool, Error> {
+    match event {
+        UserInputEvent::QuickAction1 | UserInputEvent::Activate => {
+            if is_disabled(app, node_key)? {
+                return Ok(true);
+            }
+
+            if let UserInputEvent::QuickAction1 = event {
+                app.set_focused_node(node_key)?;
+            }
+
+            press(app, m, node_key)?;
+
+            let qa_callback: Option<ArcStr> = app.attr(node_key, ON_QUICK_ACTION)?;
+            if let Some(qa_callback) = qa_callback {
+                let callback = app.callbacks.get(&qa_callback).unwrap();
+                callback(app, node_key)?;
+            }
+
+            Ok(true)
+        },
+        UserInputEvent::FocusLoss => {
+            // removes the focus ring
+            app.call_resizer(node_key)?;
+            Ok(true)
+        },
+        _ => Ok(false),
+    }
+}
+
+/// Displays a button with its `pressed` style, until a timer expires
+fn press(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    let timer = app.set_timer(node_key, PRESSED_DURATION, release);
+
+    let storage: &mut ButtonStorage = get_storage(&mut app.mutators, m).unwrap();
+    if let Some((previous, previous_timer)) = storage.pressed.replace((node_key, timer)) {
+        app.cancel_timer(previous_timer);
+        if previous != node_key && app.view.get(previous).is_some() {
+            app.call_resizer(previous)?;
+        }
+    }
+
+    app.call_resizer(node_key)
+}
+
+fn release(app: &mut Application, node_key: NodeKey) -> Result<(), Error> {
+    let m = app.view[node_key].factory.get().ok_or_else(|| error!())?;
+    let storage: &mut ButtonStorage = get_storage(&mut app.mutators, m).unwrap();
+
+    if storage.pressed.map(|(pressed, _)| pressed) == Some(node_key) {
+        storage.pressed = None;
+        app.call_resizer(node_key)?;
+    }
+
+    Ok(())
+}
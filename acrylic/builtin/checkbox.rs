@@ -0,0 +1,174 @@
+use crate::core::app::Application;
+use crate::core::event::{Handlers, DEFAULT_HANDLERS, UserInputEvent};
+use crate::core::node::{NodeKey, Mutator, MutatorIndex, get_storage};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
+use crate::core::visual::{Ratio, LayoutMode, PixelSource, RgbaPixelArray, Texture};
+use crate::core::visual::{Position, Size, Pixels, SignedPixels};
+use crate::core::rgb::{FromSlice, RGBA8};
+use crate::{SSAA, Error, error, Box, vec, ArcStr, ro_string};
+use core::mem::ManuallyDrop;
+
+use super::container::Decoration;
+use wizdraw::stroke;
+use vek::Vec2;
+
+const CHECKED: usize = 0;
+
+const ATTR_SET: &[(&str, AttributeValueType, Option<ManuallyDrop<ArcStr>>)] = &[
+    ("checked", AttributeValueType::Boolean, None),
+];
+
+const HANDLERS: Handlers = Handlers {
+    initializer,
+    populator,
+    resizer,
+    user_input_handler,
+    ..DEFAULT_HANDLERS
+};
+
+pub const CHECKBOX_MUTATOR: Mutator = Mutator {
+    name: ro_string!("CheckboxMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("checkbox"),
+        attr_set: ATTR_SET,
+        accepts_children: false,
+        text_content: None,
+    }),
+    handlers: HANDLERS,
+    storage: None,
+};
+
+pub const TOGGLE_MUTATOR: Mutator = Mutator {
+    name: ro_string!("ToggleMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("toggle"),
+        attr_set: ATTR_SET,
+        accepts_children: false,
+        text_content: None,
+    }),
+    handlers: HANDLERS,
+    storage: None,
+};
+
+fn is_toggle(app: &Application, node_key: NodeKey) -> bool {
+    &*app.xml_tag(node_key) == "toggle"
+}
+
+fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, _: XmlNodeKey) -> Result<(), Error> {
+    let ratio = match is_toggle(app, node_key) {
+        true => Ratio::from_num(2),
+        false => Ratio::ONE,
+    };
+
+    app.view[node_key].config.set_layout_mode(LayoutMode::AspectRatio(ratio));
+    app.invalidate_layout();
+
+    Ok(())
+}
+
+fn initializer(app: &mut Application, m: MutatorIndex) -> Result<(), Error> {
+    let storage = &mut app.mutators[usize::from(m)].storage;
+    assert!(storage.is_none());
+
+    *storage = Some(Box::new(Decoration::new()));
+
+    Ok(())
+}
+
+fn resizer(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    let checked: bool = app.attr(node_key, CHECKED)?;
+    let toggle = is_toggle(app, node_key);
+
+    let style_name = match checked {
+        true => "incite-focus",
+        false => "incite-inert",
+    };
+
+    let style_index = app.theme.resolve(style_name).ok_or_else(|| error!("Missing {} style in theme", style_name))?;
+    let style = app.theme.get(style_index);
+    let ext = app.get_inherited_style(node_key)?.background;
+
+    let size = app.view[node_key].size;
+    let (w, h): (usize, usize) = (size.w.to_num(), size.h.to_num());
+    let border = (h / 12).max(1);
+
+    let radius = match toggle {
+        true => h / 2,
+        false => h / 5,
+    };
+
+    let mut canvas = vec![0u8; w * h * 4];
+    let pixels = canvas.as_rgba_mut();
+    let decoration: &mut Decoration = get_storage(&mut app.mutators, m).unwrap();
+
+    if toggle {
+        // the knob: a square of the foreground color, with corners of the background color
+        let diameter = h.saturating_sub(4 * border);
+        let x = match checked {
+            true => w.saturating_sub(2 * border + diameter),
+            false => 2 * border,
+        };
+
+        let px = |n: usize| Pixels::from_num(n);
+        let knob_size = Size::new(px(diameter), px(diameter));
+        let knob = (Position::new(SignedPixels::from_num(x), SignedPixels::from_num(2 * border)), knob_size);
+
+        PixelSource::SolidColor(style.foreground).paint(pixels, knob, knob, w, false, true);
+        let corners = decoration.render(knob_size, Pixels::ZERO, px(diameter / 2), None, style.foreground, style.background);
+        corners.paint(pixels, knob, knob, w, true, true);
+    } else if checked {
+        // the check mark; strokes must be closed paths
+        let (wf, hf) = (w as f32, h as f32);
+        let a = Vec2::new(wf * 0.27, hf * 0.52);
+        let b = Vec2::new(wf * 0.43, hf * 0.68);
+        let c = Vec2::new(wf * 0.73, hf * 0.34);
+
+        let mut mask = vec![0u8; w * h];
+        stroke::<SSAA>(&[a, b, c, b, a], &mut mask, Vec2::new(w, h), hf / 8.0);
+
+        for (pixel, coverage) in pixels.iter_mut().zip(mask) {
+            let alpha = (coverage as u32 * style.foreground.a as u32) / 255;
+            *pixel = RGBA8 { a: alpha as u8, ..style.foreground };
+        }
+    }
+
+    // the outline & the rounded corners
+    let frame = decoration.render(size, Pixels::ZERO, Pixels::from_num(radius), Some(Pixels::from_num(border)), style.outline, ext);
+    let whole = (Position::zero(), size);
+    frame.paint(pixels, whole, whole, w, true, true);
+
+    app.view[node_key].background = PixelSource::SolidColor(style.background);
+    app.view[node_key].config.set_dirty(true);
+    app.view[node_key].foreground = {
+        let canvas = canvas.into_boxed_slice();
+        PixelSource::TextureNoSSAA(Box::new(RgbaPixelArray::new(canvas, w, h)))
+    };
+
+    Ok(())
+}
+
+fn user_input_handler(
+    app: &mut Application,
+    _m: MutatorIndex,
+    node_key: NodeKey,
+    _target: NodeKey,
+    event: &UserInputEvent,
+) -> Result<bool, Error> {
+    if let UserInputEvent::QuickAction1 = event {
+        let checked_path = match app.attr_state_path(node_key, CHECKED)? {
+            Ok((path, _)) => path,
+            Err(_) => {
+                log::error!("Cannot flip <{}>: \"checked\" isn't a state path", app.xml_tag(node_key));
+                return Ok(true);
+            },
+        };
+
+        let checked: bool = app.attr(node_key, CHECKED)?;
+        app.state.set_boolean(&checked_path, !checked);
+        app.reload_view();
+
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
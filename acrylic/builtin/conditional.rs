@@ -0,0 +1,235 @@
+use crate::core::app::Application;
+use crate::core::event::{Handlers, DEFAULT_HANDLERS};
+use crate::core::node::{NodeKey, Mutator, MutatorIndex};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValue, AttributeValueType};
+use crate::core::visual::LayoutMode;
+use crate::core::for_each_child;
+use crate::{Error, error, ArcStr, ro_string};
+use oakwood::NodeKey as _;
+
+const TEST:  usize = 0;
+const VALUE: usize = 0;
+
+pub const IF_MUTATOR: Mutator = Mutator {
+    name: ro_string!("IfMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("if"),
+        attr_set: &[ ("test", AttributeValueType::Other, None) ],
+        accepts_children: true,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        populator: if_populator,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+pub const ELSE_MUTATOR: Mutator = Mutator {
+    name: ro_string!("ElseMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("else"),
+        attr_set: &[],
+        accepts_children: true,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        populator: else_populator,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+pub const SWITCH_MUTATOR: Mutator = Mutator {
+    name: ro_string!("SwitchMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("switch"),
+        attr_set: &[ ("value", AttributeValueType::Other, None) ],
+        accepts_children: true,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        populator: switch_populator,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+pub const CASE_MUTATOR: Mutator = Mutator {
+    name: ro_string!("CaseMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("case"),
+        attr_set: &[ ("value", AttributeValueType::OptOther, None) ],
+        accepts_children: true,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        populator: case_populator,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+fn is_tag(app: &Application, xml_node_key: XmlNodeKey, mutator: &Mutator) -> bool {
+    match app.xml_tree[xml_node_key].factory.get() {
+        Some(i) => app.mutators[usize::from(i)].name == mutator.name,
+        None => false,
+    }
+}
+
+fn only_child(app: &Application, xml_node_key: XmlNodeKey, tag: &str) -> Result<XmlNodeKey, Error> {
+    match app.xml_tree.first_child(xml_node_key) {
+        Some(xml_child) if app.xml_tree.is_only_child(xml_child) => Ok(xml_child),
+        _ => Err(error!("{}: <{}> must have exactly one XML child", app.xml_location(xml_node_key), tag)),
+    }
+}
+
+fn is_conditional(app: &Application, xml_node_key: XmlNodeKey) -> bool {
+    [&IF_MUTATOR, &ELSE_MUTATOR, &SWITCH_MUTATOR, &CASE_MUTATOR].into_iter().any(|m| is_tag(app, xml_node_key, m))
+}
+
+/// Finds the outermost `<if>`, `<else>` or `<switch>` which
+/// displays `xml_node_key`, or returns `xml_node_key` itself.
+///
+/// Nodes are re-populated from there, so that conditions are evaluated again.
+pub(crate) fn conditional_source(app: &Application, mut xml_node_key: XmlNodeKey) -> XmlNodeKey {
+    while let Some(xml_parent) = app.xml_tree.parent(xml_node_key) {
+        match is_conditional(app, xml_parent) {
+            true => xml_node_key = xml_parent,
+            false => break,
+        }
+    }
+
+    xml_node_key
+}
+
+/// Checks if a node displaying `xml_node_key` has lookups in the
+/// `ns_name` namespace, including in the conditions leading to it
+///
+/// An `<else>` depends on the condition of the preceding `<if>`.
+pub(crate) fn uses_namespace(app: &Application, mut xml_node_key: XmlNodeKey, ns_name: &str) -> bool {
+    let source = conditional_source(app, xml_node_key);
+
+    loop {
+        let uses = |xml_node_key| app.xml_tree[xml_node_key].attributes.uses_namespace(ns_name);
+        if uses(xml_node_key) {
+            return true;
+        }
+
+        if is_tag(app, xml_node_key, &ELSE_MUTATOR) && uses(app.xml_tree.prev_sibling(xml_node_key)) {
+            return true;
+        }
+
+        match xml_node_key == source {
+            true => return false,
+            false => xml_node_key = app.xml_tree.parent(xml_node_key).unwrap(),
+        }
+    }
+}
+
+/// The node becomes the view of `xml_child`, or is left out of the layout
+///
+/// In the latter case, it keeps the XML node of the conditional,
+/// which tells a following `<else>` that the condition didn't hold.
+fn show(app: &mut Application, node_key: NodeKey, xml_child: Option<XmlNodeKey>) -> Result<(), Error> {
+    match xml_child {
+        Some(xml_child) => {
+            app.view[node_key].xml_node_index = Some(xml_child.index()).into();
+            app.view[node_key].factory = app.xml_tree[xml_child].factory;
+            app.call_populator(node_key, xml_child)
+        },
+        None => {
+            app.view[node_key].config.set_layout_mode(LayoutMode::Unset);
+            Ok(())
+        },
+    }
+}
+
+fn if_populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    let xml_child = only_child(app, xml_node_key, "if")?;
+    let condition = app.attr_condition(node_key, TEST)?;
+    show(app, node_key, condition.then_some(xml_child))
+}
+
+/// An `<if>` whose condition doesn't hold keeps its XML node
+fn else_populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    let xml_child = only_child(app, xml_node_key, "else")?;
+    let xml_parent = app.xml_tree.parent(xml_node_key).unwrap();
+
+    let xml_if = match app.xml_tree.first_child(xml_parent) == Some(xml_node_key) {
+        true => None,
+        false => Some(app.xml_tree.prev_sibling(xml_node_key)),
+    };
+
+    let xml_if = match xml_if {
+        Some(xml_if) if is_tag(app, xml_if, &IF_MUTATOR) => xml_if,
+        _ => return Err(error!("{}: <else> must follow an <if>", app.xml_location(xml_node_key))),
+    };
+
+    let prev_node = app.view.prev_sibling(node_key);
+    let if_failed = app.view[prev_node].xml_node_index.get() == Some(xml_if.index());
+    show(app, node_key, if_failed.then_some(xml_child))
+}
+
+fn switch_populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    let value: ArcStr = app.attr(node_key, VALUE)?;
+    let mut selected = None;
+
+    for_each_child!(app.xml_tree, xml_node_key, xml_case, {
+        if !is_tag(app, xml_case, &CASE_MUTATOR) {
+            return Err(error!("{}: <switch> can only contain <case> tags", app.xml_location(xml_case)));
+        }
+
+        let matches = match app.xml_tree[xml_case].attributes.get(VALUE) {
+            AttributeValue::OptOther(Some(case_value)) => *case_value == value,
+            AttributeValue::StateLookup { .. } | AttributeValue::Interpolation { .. } => {
+                return Err(error!("{}: <case> values cannot be JSON state lookups", app.xml_location(xml_case)));
+            },
+            // default case
+            _ => true,
+        };
+
+        if matches && selected.is_none() {
+            selected = Some(xml_case);
+        }
+    });
+
+    // cases can be empty
+    let xml_child = match selected {
+        Some(xml_case) if app.xml_tree.first_child(xml_case).is_some() => Some(only_child(app, xml_case, "case")?),
+        _ => None,
+    };
+
+    show(app, node_key, xml_child)
+}
+
+fn case_populator(app: &mut Application, _: MutatorIndex, _: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    Err(error!("{}: <case> tags must be children of a <switch>", app.xml_location(xml_node_key)))
+}
+
+#[test]
+fn refreshes_conditionals() {
+    use crate::core::app::test_app;
+    use lmfu::json::parse_path;
+
+    let xml = r#"
+        <v-rem>
+            <if root:test="unread"><h-rem /></if>
+            <else><v-rem /></else>
+        </v-rem>
+    "#;
+
+    let mut app = test_app(r#"{ "unread": true }"#, xml);
+    let if_node = app.view.first_child(app.root).unwrap();
+    let else_node = app.view.next_sibling(if_node);
+    let is_shown = |app: &Application, node_key| app.view[node_key].config.get_layout_mode() != LayoutMode::Unset;
+    assert!(is_shown(&app, if_node) && !is_shown(&app, else_node));
+
+    app.state.set_boolean(&parse_path("unread").into(), false);
+    app.refresh_namespace(app.root, "root").unwrap();
+    assert!(!is_shown(&app, if_node) && is_shown(&app, else_node));
+
+    app.state.set_boolean(&parse_path("unread").into(), true);
+    app.refresh_namespace(app.root, "root").unwrap();
+    assert!(is_shown(&app, if_node) && !is_shown(&app, else_node));
+}
@@ -1,14 +1,17 @@
 use crate::core::app::Application;
 use crate::core::event::{Handlers, DEFAULT_HANDLERS, UserInputEvent};
-use crate::core::state::Namespace;
+use crate::core::state::{Namespace, Item, select_item, select_items};
 use crate::core::node::{NodeKey, Mutator, MutatorIndex, get_storage};
-use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
-use crate::core::visual::{Pixels, SignedPixels, Margin, Axis, LayoutMode, PixelSource, RgbaPixelArray};
-use crate::core::{for_each_child, rgb::FromSlice};
-use crate::core::layout::{get_scroll, scroll};
-use crate::{SSAA, SSAA_SQ, Error, error, Box, Vec, ArcStr, ro_string};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType, locate};
+use crate::core::visual::{Pixels, SignedPixels, Margin, Axis, LayoutMode, Overflow, PixelSource, RgbaPixelArray, Size, Position, MAX_CONTENT_GAP};
+use crate::core::{for_each_child, rgb::{FromSlice, RGBA8}};
+use crate::core::layout::{get_scrollbar, scroll_by, Scrollbar};
+use crate::core::transition::{self, Transition};
+use crate::{SSAA, SSAA_SQ, FALSE_STR, Error, error, Box, Vec, vec, ArcStr, ro_string};
 use oakwood::NodeKey as _;
 use lmfu::json::{Value, Path};
+use core::{mem::ManuallyDrop, time::Duration};
+use vek::num_traits::Float;
 
 use railway::{NaiveRenderer, computing::{Couple, C_ZERO}};
 
@@ -37,13 +40,176 @@ fn initializer(app: &mut Application, m: MutatorIndex) -> Result<(), Error> {
     let storage = &mut app.mutators[usize::from(m)].storage;
     assert!(storage.is_none());
 
-    let railway = R::parse(include_bytes!(concat!(env!("OUT_DIR"), "/container.rwy"))).unwrap();
-    *storage = Some(Box::new((railway, Vec::<u8>::new())));
+    *storage = Some(Box::new(ContainerStorage {
+        decoration: Decoration::new(),
+        drag: None,
+        flings: Vec::new(),
+        lists: Vec::new(),
+        selections: Vec::new(),
+    }));
 
     Ok(())
 }
 
-fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+struct ContainerStorage {
+    decoration: Decoration,
+    /// Container receiving Drag events
+    drag: Option<(NodeKey, DragMode)>,
+    /// Containers which keep scrolling after a drag
+    flings: Vec<Fling>,
+    /// Virtualized generators
+    lists: Vec<VirtualList>,
+    /// Generators with `where` or `sort-by` attributes & the indices of their items
+    selections: Vec<(NodeKey, Vec<usize>)>,
+}
+
+#[derive(Copy, Clone)]
+enum DragMode {
+    Thumb,
+    Content {
+        /// Pointer coordinate on the scroll axis, at the last Drag event
+        pointer: SignedPixels,
+        /// Last velocity sample, in pixels per second
+        sample: (SignedPixels, Duration),
+        velocity: f32,
+    },
+}
+
+struct Fling {
+    node_key: NodeKey,
+    /// In pixels per second
+    velocity: f32,
+    last_step: Duration,
+}
+
+/// Generator which only instantiates the items around its viewport
+///
+/// Its first & last children are spacers standing for the items
+/// which aren't instantiated; see [`update_window`].
+struct VirtualList {
+    node_key: NodeKey,
+    xml_child: XmlNodeKey,
+    /// Name of the namespace of items (the `for` attribute)
+    namespace: ArcStr,
+    len: usize,
+    /// Range of instantiated items
+    first: usize,
+    end: usize,
+    item_length: Option<Pixels>,
+    /// Measured item lengths (zero if unknown), unless `item_length` is set
+    lengths: Vec<Pixels>,
+    /// Prefix sums of `lengths`
+    measures: Measures,
+    /// Sum of `lengths` & number of measured items
+    measured: (f64, usize),
+    /// First visible item, its expected offset in the content & the scroll offset then
+    anchor: Option<(usize, f64, SignedPixels)>,
+}
+
+/// Fenwick tree of the measured lengths of the items of a virtual list
+///
+/// Sums of lengths and numbers of measured items, from the
+/// first item to any other, are obtained in logarithmic time.
+struct Measures {
+    tree: Vec<(f64, usize)>,
+}
+
+impl Measures {
+    fn new(len: usize) -> Self {
+        Self {
+            tree: vec![(0.0, 0); len],
+        }
+    }
+
+    fn add(&mut self, index: usize, length: f64, count: usize) {
+        let mut i = index + 1;
+        while i <= self.tree.len() {
+            self.tree[i - 1].0 += length;
+            self.tree[i - 1].1 += count;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sum of lengths & number of measured items in `0..end`
+    fn prefix(&self, end: usize) -> (f64, usize) {
+        let (mut length, mut count) = (0.0, 0);
+        let mut i = end.min(self.tree.len());
+        while i > 0 {
+            length += self.tree[i - 1].0;
+            count += self.tree[i - 1].1;
+            i &= i - 1;
+        }
+
+        (length, count)
+    }
+}
+
+fn storage(mutators: &mut [Mutator], m: MutatorIndex) -> &mut ContainerStorage {
+    get_storage(mutators, m).unwrap()
+}
+
+/// Railway program drawing the border & rounded corners of containers
+pub(crate) struct Decoration {
+    railway: R,
+    mask: Vec<u8>,
+}
+
+impl Decoration {
+    pub(crate) fn new() -> Self {
+        let railway = R::parse(include_bytes!(concat!(env!("OUT_DIR"), "/container.rwy"))).unwrap();
+        Self {
+            railway,
+            mask: Vec::new(),
+        }
+    }
+
+    /// Renders the decoration of a node
+    ///
+    /// `ext` is the color outside of the rounded corners.
+    pub(crate) fn render(
+        &mut self,
+        size: Size,
+        margin: Pixels,
+        radius: Pixels,
+        border_width: Option<Pixels>,
+        border: RGBA8,
+        ext: RGBA8,
+    ) -> PixelSource {
+        let (w, h): (usize, usize) = (size.w.to_num(), size.h.to_num());
+        let couple = Couple::new(w as f32, h as f32);
+
+        let ext_rg = Couple::new((ext.r as f32) / 255.0, (ext.g as f32) / 255.0);
+        let ext_ba = Couple::new((ext.b as f32) / 255.0, (ext.a as f32) / 255.0);
+
+        let border_rg = Couple::new((border.r as f32) / 255.0, (border.g as f32) / 255.0);
+        let border_ba = Couple::new((border.b as f32) / 255.0, (border.a as f32) / 255.0);
+
+        let border_width = match border_width {
+            Some(sfr) => Couple::new(sfr.to_num(), 0.0),
+            None => C_ZERO,
+        };
+
+        let railway = &mut self.railway;
+        railway.set_argument("size", couple).unwrap();
+        railway.set_argument("margin-radius", Couple::new(margin.to_num(), radius.to_num())).unwrap();
+        railway.set_argument("border-width", border_width).unwrap();
+        railway.set_argument("border-rg", border_rg).unwrap();
+        railway.set_argument("border-ba", border_ba).unwrap();
+        railway.set_argument("ext-rg", ext_rg).unwrap();
+        railway.set_argument("ext-ba", ext_ba).unwrap();
+        railway.compute().unwrap();
+
+        let length = w * h;
+        let mut canvas: Vec<u8> = vec![0; length * 4];
+        self.mask.resize(length, 0);
+        railway.render::<SSAA, SSAA_SQ>(canvas.as_rgba_mut(), &mut self.mask, w, h, w, true).unwrap();
+
+        let canvas = canvas.into_boxed_slice();
+        PixelSource::TextureNoSSAA(Box::new(RgbaPixelArray::new(canvas, w, h)))
+    }
+}
+
+fn populator(app: &mut Application, m: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
     let    for_attr: Option<ArcStr> = app.attr(node_key,             FOR)?;
     let     in_attr: Option<ArcStr> = app.attr(node_key,              IN)?;
     let  style_attr: Option<ArcStr> = app.attr(node_key,           STYLE)?;
@@ -52,17 +218,35 @@ fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node
     let content_gap: Pixels         = app.attr(node_key,             GAP)?;
     let margin_attr: Pixels         = app.attr(node_key,          MARGIN)?;
     let radius_attr: Pixels         = app.attr(node_key,   BORDER_RADIUS)?;
+    let  transition: Option<ArcStr> = app.attr(node_key,      TRANSITION)?;
+    let    overflow: ArcStr         = app.attr(node_key,        OVERFLOW)?;
+    let virtual_attr: bool          = app.attr(node_key,         VIRTUAL)?;
+    let item_length: Option<Pixels> = app.attr(node_key,     ITEM_LENGTH)?;
+    let  where_attr: Option<ArcStr> = app.attr(node_key,           WHERE)?;
+    let     sort_by: Option<ArcStr> = app.attr(node_key,         SORT_BY)?;
 
     let xml_node = &app.xml_tree[xml_node_key];
     let mutator_index = xml_node.factory.get().unwrap();
     let mutator = &app.mutators[usize::from(mutator_index)];
     let tag = mutator.xml_params.as_ref().unwrap().tag_name.clone();
+    let location = app.xml_location(xml_node_key);
 
     let (content_axis, layout_mode) = parse_tag(app, node_key, &*tag)?;
 
+    let overflow = match &*overflow {
+        "visible" => Overflow::Visible,
+        "clip" => Overflow::Clip,
+        "scroll" => Overflow::Scroll,
+        _ => return Err(error!("{}: Invalid overflow: {} (expected visible, clip or scroll)", location, overflow)),
+    };
+
+    if content_gap > MAX_CONTENT_GAP {
+        return Err(error!("{}: Gap is too large: {} (max: {})", location, content_gap, MAX_CONTENT_GAP));
+    }
+
     if let Some(qa_callback) = qa_callback {
         if !app.callbacks.contains_key(&qa_callback) {
-            return Err(error!("Unknown callback: {}", qa_callback));
+            return Err(error!("{}: Unknown callback: {}", location, qa_callback));
         }
     }
 
@@ -70,12 +254,13 @@ fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node
     app.view[node_key].config.set_content_axis(content_axis);
     app.view[node_key].config.set_content_gap(content_gap);
     app.view[node_key].config.set_layout_mode(layout_mode);
+    app.view[node_key].overflow = overflow;
     app.invalidate_layout();
 
     if let Some(style) = style_attr {
         let style_index = match app.theme.resolve(&style) {
             Some(index) => Ok(index),
-            None => Err(error!("Invalid style name")),
+            None => Err(error!("{}: Invalid style name: {}", location, style)),
         }?;
 
         let color = app.theme.get(style_index).background;
@@ -87,151 +272,454 @@ fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node
         app.view[node_key].config.set_hover_sensitivity(true);
     }
 
+    if let Some(transition) = transition {
+        app.view[node_key].transition = Some(Box::new(Transition::parse(&transition).map_err(|e| locate(e, &location))?));
+    }
+
     let to_generate = if let Some(new_ns_name) = for_attr {
         let namespace_path = match in_attr {
             Some(cs) => Ok(cs),
-            None => Err(error!("<{} for=... in=...> - missing \"in\" attribute", &*tag)),
+            None => Err(error!("{}: <{} for=... in=...> - missing \"in\" attribute", location, &*tag)),
         }?;
 
         if let Some((parent_ns_name, parent_ns_path)) = namespace_path.split_once(':') {
-            fn callback(
-                app: &Application,
-                ns_creator: NodeKey,
-                ns_user: NodeKey,
-                path: &mut Path,
-            ) -> Result<(), Error> {
-                let mut child = ns_user;
-                loop {
-                    let parent = app.view.parent(child).unwrap();
-                    match parent == ns_creator {
-                        true => break,
-                        false => child = parent,
-                    }
-                }
-
-                let index = app.view.child_index(child).unwrap();
-                path.index_num(index);
-
-                Ok(())
-            }
-
             let path = app.resolve(node_key, parent_ns_name, parent_ns_path)?;
 
             let len = match &app.state[&path] {
                 Value::Array(vector_len) => *vector_len,
-                _ => return Err(error!("Generator: {}:{} is not an array", parent_ns_name, parent_ns_path)),
+                Value::Object(keys) => keys.len(),
+                _ => return Err(error!("{}: Generator: {}:{} is not an array or an object", location, parent_ns_name, parent_ns_path)),
             };
 
-            app.namespaces.insert(node_key, Namespace {
-                name: new_ns_name,
+            let len = match where_attr.is_some() || sort_by.is_some() {
+                true => {
+                    let indices = select_items(&app.state, &path, len, where_attr.as_deref(), sort_by.as_deref())
+                        .map_err(|e| locate(e, &location))?;
+                    let len = indices.len();
+                    let selections = &mut storage(&mut app.mutators, m).selections;
+
+                    // selections of nodes which disappeared in a reload,
+                    // or of this node before its namespace was refreshed
+                    selections.retain(|(generator, _)| *generator != node_key && app.view.get(*generator).is_some());
+                    selections.push((node_key, indices));
+                    len
+                },
+                false => len,
+            };
+
+            app.add_namespace(node_key, Namespace {
+                name: new_ns_name.clone(),
                 path,
-                callback,
+                callback: match virtual_attr {
+                    true => virtual_item_index,
+                    false => item_index,
+                },
             });
 
-            Some(len)
+            Some((len, new_ns_name))
         } else {
-            return Err(error!("<{} for=... in=...> - missing colon in \"in\"", &*tag));
+            return Err(error!("{}: <{} for=... in=...> - missing colon in \"in\"", location, &*tag));
         }
     } else if in_attr.is_some() {
-        return Err(error!("<{} for=... in=...> - missing \"for\" attribute", &*tag));
+        return Err(error!("{}: <{} for=... in=...> - missing \"for\" attribute", location, &*tag));
+    } else if where_attr.is_some() || sort_by.is_some() {
+        return Err(error!("{}: <{} where=... sort-by=...> - only generators can filter & sort", location, &*tag));
     } else {
         None
     };
 
-    for_each_child!(app.xml_tree, xml_node_key, xml_child, {
-        let xml_node_index = Some(xml_child.index()).into();
-        let add_child = |app: &mut Application| -> Result<_, _> {
+    if virtual_attr {
+        let (len, namespace) = to_generate.ok_or_else(|| error!("{}: <{} virtual> - only generators can be virtual", location, &*tag))?;
+
+        if overflow != Overflow::Scroll {
+            return Err(error!("{}: <{} virtual> - overflow must be \"scroll\"", location, &*tag));
+        }
+
+        if let LayoutMode::Chunks(_) = layout_mode {
+            return Err(error!("{}: <{} virtual> - chunks cannot be virtual", location, &*tag));
+        }
+
+        let xml_child = match app.xml_tree.first_child(xml_node_key) {
+            Some(xml_child) if app.xml_tree.is_only_child(xml_child) => xml_child,
+            _ => return Err(error!("{}: Virtual generators must have exactly one XML child", location)),
+        };
+
+        // items are instantiated between these two spacers
+        let estimate = item_length.map(|l| l.to_num()).unwrap_or(DEFAULT_ITEM_LENGTH);
+        let total_length = (estimate + content_gap.to_num::<f32>()) * (len as f32);
+
+        for length in [0.0, total_length] {
+            let spacer = app.view.create();
+            app.view[spacer].factory = Some(m).into();
+            app.view[spacer].config.set_layout_mode(LayoutMode::Fixed(Pixels::saturating_from_num(length)));
+            app.view.append_children(spacer, node_key);
+        }
+
+        let lists = &mut storage(&mut app.mutators, m).lists;
+
+        // lists of nodes which disappeared in a reload,
+        // or of this node before its namespace was refreshed
+        lists.retain(|list| list.node_key != node_key && app.view.get(list.node_key).is_some());
+        let measured_len = match item_length {
+            Some(_) => 0,
+            None => len,
+        };
+
+        lists.push(VirtualList {
+            node_key,
+            xml_child,
+            namespace,
+            len,
+            first: 0,
+            end: 0,
+            item_length,
+            lengths: vec![Pixels::ZERO; measured_len],
+            measures: Measures::new(measured_len),
+            measured: (0.0, 0),
+            anchor: None,
+        });
+
+        return Ok(());
+    }
+
+    // generators repeat all XML children for each item
+    for _ in 0..to_generate.map(|(len, _)| len).unwrap_or(1) {
+        for_each_child!(app.xml_tree, xml_node_key, xml_child, {
             let child_node = app.view.create();
             app.view.append_children(child_node, node_key);
-            app.view[child_node].xml_node_index = xml_node_index;
+            app.view[child_node].xml_node_index = Some(xml_child.index()).into();
             app.view[child_node].factory = app.xml_tree[xml_child].factory;
 
-            app.call_populator(child_node, xml_child)
-        };
+            app.call_populator(child_node, xml_child)?;
+        });
+    }
 
-        if let Some(to_generate) = to_generate {
-            if app.xml_tree.is_only_child(xml_child) {
-                for _ in 0..to_generate {
-                    add_child(app)?;
-                }
-            } else {
-                return Err(error!("Generators cannot have more than one XML child"));
-            }
-        } else {
-            add_child(app)?;
+    Ok(())
+}
+
+/// Finds the child of a generator which contains `ns_user`
+fn generated_child(app: &Application, ns_creator: NodeKey, ns_user: NodeKey) -> NodeKey {
+    let mut child = ns_user;
+    loop {
+        let parent = app.view.parent(child).unwrap();
+        match parent == ns_creator {
+            true => break child,
+            false => child = parent,
         }
+    }
+}
+
+/// Storage of the mutator of a container
+fn container_storage(app: &Application, node_key: NodeKey) -> &ContainerStorage {
+    let m = app.view[node_key].factory.get().unwrap();
+    let storage = app.mutators[usize::from(m)].storage.as_ref();
+    storage.and_then(|s| s.downcast_ref::<ContainerStorage>()).unwrap()
+}
+
+/// Appends the path of the item at `position` in a generator to `path`,
+/// skipping the items which were filtered out by `where` and `sort-by`
+fn select(app: &Application, generator: NodeKey, path: &mut Path, position: usize) -> Result<Option<Item>, Error> {
+    let selections = &container_storage(app, generator).selections;
+    let index = match selections.iter().find(|(node_key, _)| *node_key == generator) {
+        Some((_, indices)) => *indices.get(position).ok_or_else(|| error!())?,
+        None => position,
+    };
+
+    select_item(&app.state, path, index).map(Some)
+}
+
+fn item_index(app: &Application, ns_creator: NodeKey, ns_user: NodeKey, path: &mut Path) -> Result<Option<Item>, Error> {
+    let child = generated_child(app, ns_creator, ns_user);
+    let index = app.view.child_index(child).unwrap();
+
+    // each item has one node per XML child
+    let xml_node_index = app.view[ns_creator].xml_node_index.get().unwrap();
+    let mut template_len = 0;
+    for_each_child!(app.xml_tree, app.xml_tree.node_key(xml_node_index), _xml_child, {
+        template_len += 1;
     });
 
+    select(app, ns_creator, path, index / template_len)
+}
+
+fn virtual_item_index(app: &Application, ns_creator: NodeKey, ns_user: NodeKey, path: &mut Path) -> Result<Option<Item>, Error> {
+    let child = generated_child(app, ns_creator, ns_user);
+    let index = app.view.child_index(child).unwrap();
+
+    let lists = &container_storage(app, ns_creator).lists;
+    let list = lists.iter().find(|list| list.node_key == ns_creator).ok_or_else(|| error!())?;
+
+    // the first child is a spacer
+    select(app, ns_creator, path, list.first + index - 1)
+}
+
+fn scroller(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    let lists = &storage(&mut app.mutators, m).lists;
+    match lists.iter().position(|list| list.node_key == node_key) {
+        Some(i) => update_window(app, m, node_key, i),
+        None => Ok(()),
+    }
+}
+
+/// Instantiates the items of a virtual list which intersect
+/// its viewport (plus [`OVERSCAN`] items on each side).
+///
+/// Items which leave this window are recycled: they're moved to
+/// the other end of the window, where their nodes which depend on
+/// the item (see [`Application::refresh_namespace`]) are re-created.
+///
+/// Items which weren't measured yet are assumed to be as long
+/// as the average measured item. When their real length differs,
+/// the scroll offset is corrected so that the first visible item
+/// stays where it was displayed.
+fn update_window(app: &mut Application, m: MutatorIndex, node_key: NodeKey, i: usize) -> Result<(), Error> {
+    let node = &app.view[node_key];
+    let axis = node.config.get_length_axis();
+    let gap = node.config.get_content_gap().to_num::<f64>();
+    let viewport = match node.size.get_for_axis(axis).checked_sub(node.margin.total_on(axis)) {
+        Some(viewport) if !viewport.is_zero() => viewport.to_num::<f64>(),
+        _ => return Ok(()),
+    };
+
+    let head = app.view.first_child(node_key).unwrap();
+    let tail = app.view.last_child(node_key).unwrap();
+
+    let mut items = Vec::new();
+    let mut child = app.view.next_sibling(head);
+    while child != tail {
+        items.push(child);
+        child = app.view.next_sibling(child);
+    }
+
+    // offset of a child from the start of the content
+    let offset_of = |child: NodeKey| {
+        let position = app.view[child].position.get_for_axis(axis);
+        (position - app.view[head].position.get_for_axis(axis)).to_num::<f64>()
+    };
+
+    let list = &mut storage(&mut app.mutators, m).lists[i];
+
+    if list.item_length.is_none() {
+        for (k, item) in items.iter().enumerate() {
+            let (i, length) = (list.first + k, app.view[*item].size.get_for_axis(axis));
+            let old = list.lengths[i];
+
+            if !length.is_zero() && length != old {
+                let diff = length.to_num::<f64>() - old.to_num::<f64>();
+                let count = usize::from(old.is_zero());
+                list.measures.add(i, diff, count);
+                list.measured.0 += diff;
+                list.measured.1 += count;
+                list.lengths[i] = length;
+            }
+        }
+    }
+
+    // items were measured by a new layout: keep the anchor in place
+    let mut scroll = app.view[node_key].scroll;
+    if let Some((k, expected, anchored_scroll)) = list.anchor {
+        if anchored_scroll == scroll && (list.first..list.end).contains(&k) {
+            let drift = offset_of(items[k - list.first]) - expected;
+            let corrected = (scroll.to_num::<f64>() + drift).max(0.0);
+            scroll = SignedPixels::saturating_from_num(corrected);
+        }
+    }
+
+    let estimate = match (list.item_length, list.measured) {
+        (Some(length), _) => length.to_num::<f64>(),
+        (None, (_, 0)) => DEFAULT_ITEM_LENGTH as f64,
+        (None, (sum, count)) => sum / (count as f64),
+    };
+
+    // length of the items before `i`, each followed by a gap
+    let measures = &list.measures;
+    let prefix = |i: usize| {
+        let (sum, count) = measures.prefix(i);
+        sum + ((i - count) as f64) * estimate + (i as f64) * gap
+    };
+
+    let len = list.len;
+    let start = scroll.to_num::<f64>();
+    let stop = start + viewport;
+
+    // the head spacer is followed by a gap
+    let visible_end = search(len, |i| gap + prefix(i) >= stop);
+    let visible_start = search(len, |i| prefix(i + 1) > start);
+    let anchor = match visible_start < visible_end {
+        true => Some((visible_start, gap + prefix(visible_start), scroll)),
+        false => None,
+    };
+
+    let first = visible_start.min(visible_end).saturating_sub(OVERSCAN);
+    let end = (visible_end + OVERSCAN).min(len);
+
+    let head_length = prefix(first);
+    let tail_length = prefix(len) - prefix(end);
+
+    let (old_first, old_end) = (list.first, list.end);
+    let xml_child = list.xml_child;
+    let namespace = list.namespace.clone();
+    list.first = first;
+    list.end = end;
+    list.anchor = anchor;
+
+    // items which leave the window are detached, to be recycled
+    let mut recycled = Vec::new();
+    for (k, item) in items.into_iter().enumerate() {
+        let i = old_first + k;
+        if i < first || i >= end {
+            recycled.push(app.detach_node(item)?);
+        }
+    }
+
+    let mut changed = !recycled.is_empty();
+
+    // items are inserted in order, so that their index can be resolved
+    let (kept_first, kept_end) = (first.max(old_first), end.min(old_end));
+    let front_end = match kept_first < kept_end {
+        true => kept_first,
+        false => end,
+    };
+
+    let mut prev = head;
+    for _ in first..front_end {
+        prev = insert_item(app, prev, xml_child, &namespace, &mut recycled)?;
+        changed = true;
+    }
+
+    if kept_first < kept_end {
+        for _ in kept_end..end {
+            insert_item(app, app.view.prev_sibling(tail), xml_child, &namespace, &mut recycled)?;
+            changed = true;
+        }
+    }
+
+    // recycled items which weren't needed
+    for item in recycled {
+        app.delete_node(item)?;
+    }
+
+    for (spacer, length) in [(head, head_length), (tail, tail_length)] {
+        let mode = LayoutMode::Fixed(Pixels::saturating_from_num(length));
+        if app.view[spacer].config.get_layout_mode() != mode {
+            app.view[spacer].config.set_layout_mode(mode);
+            changed = true;
+        }
+    }
+
+    if app.view[node_key].scroll != scroll {
+        app.view[node_key].scroll = scroll;
+        app.view[node_key].config.set_dirty(true);
+        changed = true;
+    }
+
+    if changed {
+        app.invalidate_layout();
+    }
+
     Ok(())
 }
 
+/// First index in `0..len` for which `test` holds, or `len`
+///
+/// `test` must be false, then true.
+fn search(len: usize, test: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = (low + high) / 2;
+        match test(mid) {
+            true => high = mid,
+            false => low = mid + 1,
+        }
+    }
+
+    low
+}
+
+/// Inserts an item in a virtual list, recycling a detached item if there's one
+fn insert_item(
+    app: &mut Application,
+    prev: NodeKey,
+    xml_child: XmlNodeKey,
+    namespace: &str,
+    recycled: &mut Vec<NodeKey>,
+) -> Result<NodeKey, Error> {
+    match recycled.pop() {
+        Some(item) => {
+            app.view.insert_after(prev, item);
+            app.refresh_namespace(item, namespace)?;
+            Ok(item)
+        },
+        None => add_item(app, prev, xml_child),
+    }
+}
+
+fn add_item(app: &mut Application, prev: NodeKey, xml_child: XmlNodeKey) -> Result<NodeKey, Error> {
+    let item = app.view.create();
+    app.view.insert_after(prev, item);
+    app.view[item].xml_node_index = Some(xml_child.index()).into();
+    app.view[item].factory = app.xml_tree[xml_child].factory;
+
+    app.call_populator(item, xml_child)?;
+    Ok(item)
+}
+
 fn resizer(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
-    if app.debug.skip_container_decoration {
+    // spacers of virtual lists
+    if app.view[node_key].xml_node_index.get().is_none() || app.debug.skip_container_decoration {
         return Ok(());
     }
 
     let border_width: Option<Pixels> = app.attr(node_key, BORDER_WIDTH)?;
     let hover_attr: Option<ArcStr> = app.attr(node_key, HOVER)?;
-    let mut style = app.view[node_key].style_override.get();
+    let radius: Pixels = app.attr(node_key, BORDER_RADIUS)?;
+    let own_style = app.view[node_key].style_override.get();
+    let mut style = own_style;
+    let hover_sensitive = hover_attr.is_some();
+
+    // rounded corners hide the children under them
+    let clips_corners = radius > Pixels::ZERO && app.view[node_key].overflow != Overflow::Visible;
 
     if app.is_hovered(node_key) {
         if let Some(style_name) = hover_attr {
             let style_index = match app.theme.resolve(&style_name) {
                 Some(index) => Ok(index),
-                None => Err(error!("Invalid style name")),
+                None => Err(error!("{}: Invalid style name: {}", app.node_location(node_key), style_name)),
             }?;
 
             style = Some(style_index.into()).into();
         }
     }
 
-    if style.is_some() || border_width.is_some() {
-        let margin: Pixels = app.attr(node_key,        MARGIN)?;
-        let radius: Pixels = app.attr(node_key, BORDER_RADIUS)?;
+    if style.is_some() || border_width.is_some() || hover_sensitive || clips_corners {
+        let margin: Pixels = app.attr(node_key, MARGIN)?;
         let inherited_style = app.get_inherited_style(node_key)?;
 
         let size = app.view[node_key].size;
-        let (w, h): (usize, usize) = (size.w.to_num(), size.h.to_num());
-        let couple = Couple::new(w as f32, h as f32);
-
         let ext = inherited_style.background;
-        let ext_rg = Couple::new((ext.r as f32) / 255.0, (ext.g as f32) / 255.0);
-        let ext_ba = Couple::new((ext.b as f32) / 255.0, (ext.a as f32) / 255.0);
 
-        let border = match style {
+        let target = match style {
             Some(style) => app.theme.get(style.into()),
             None => inherited_style,
-        }.outline;
-
-        let border_rg = Couple::new((border.r as f32) / 255.0, (border.g as f32) / 255.0);
-        let border_ba = Couple::new((border.b as f32) / 255.0, (border.a as f32) / 255.0);
+        };
 
-        let border_width = match border_width {
-            Some(sfr) => Couple::new(sfr.to_num(), 0.0),
-            None => C_ZERO,
+        // containers without a `style` attribute have no background
+        let target = match own_style.is_some() {
+            true => [target.background, target.outline],
+            false => [ext, target.outline],
         };
 
-        let (railway, mask): &mut (R, Vec<u8>) = get_storage(&mut app.mutators, m).unwrap();
-        railway.set_argument("size", couple).unwrap();
-        railway.set_argument("margin-radius", Couple::new(margin.to_num(), radius.to_num())).unwrap();
-        railway.set_argument("border-width", border_width).unwrap();
-        railway.set_argument("border-rg", border_rg).unwrap();
-        railway.set_argument("border-ba", border_ba).unwrap();
-        railway.set_argument("ext-rg", ext_rg).unwrap();
-        railway.set_argument("ext-ba", ext_ba).unwrap();
-        railway.compute().unwrap();
+        let [background, border] = transition::colors(app, node_key, target);
+
+        if own_style.is_some() {
+            app.view[node_key].background = PixelSource::SolidColor(background);
+        }
+
+        let decoration = &mut storage(&mut app.mutators, m).decoration;
+        let foreground = decoration.render(size, margin, radius, border_width, border, ext);
 
         app.view[node_key].config.set_dirty(true);
-        app.view[node_key].foreground = {
-            let length = w * h;
-            let mut canvas: Vec<u8> = Vec::with_capacity(length * 4);
-            canvas.resize(length * 4, 0);
-            mask.resize(length, 0);
-            railway.render::<SSAA, SSAA_SQ>(canvas.as_rgba_mut(), mask, w, h, w, true).unwrap();
-
-            let canvas = canvas.into_boxed_slice();
-            PixelSource::TextureNoSSAA(Box::new(RgbaPixelArray::new(canvas, w, h)))
-        };
+        app.view[node_key].foreground = foreground;
     }
 
     Ok(())
@@ -239,46 +727,228 @@ fn resizer(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<
 
 fn user_input_handler(
     app: &mut Application,
-    _m: MutatorIndex,
+    m: MutatorIndex,
     node_key: NodeKey,
     _target: NodeKey,
     event: &UserInputEvent,
 ) -> Result<bool, Error> {
-    if let UserInputEvent::WheelY(wheel_delta) = event {
-        let (axis, current_scroll, max_scroll) = get_scroll(app, node_key);
-        if max_scroll.is_none() {
-            return Ok(false);
-        }
-
-        let current_scroll = current_scroll.unwrap_or(SignedPixels::ZERO);
-        let max_scroll = max_scroll.unwrap().to_num::<SignedPixels>();
+    if app.view[node_key].xml_node_index.get().is_none() {
+        return Ok(false);
+    }
 
-        let mut candidate = *wheel_delta;
+    match event {
+        UserInputEvent::WheelY(wheel_delta) => wheel(app, m, node_key, None, *wheel_delta),
+        UserInputEvent::WheelX(wheel_delta) => wheel(app, m, node_key, Some(Axis::Horizontal), *wheel_delta),
+        UserInputEvent::QuickAction1 => {
+            if let Some(scrollbar) = get_scrollbar(app, node_key) {
+                if contains(scrollbar.track, app.get_focus_coords()) {
+                    drag_thumb(app, node_key, &scrollbar)?;
+                    return Ok(true);
+                }
+            }
 
-        let new_scroll = current_scroll - candidate;
-        if new_scroll > max_scroll {
-            candidate = current_scroll - max_scroll;
-        } else if new_scroll < SignedPixels::ZERO {
-            candidate = current_scroll;
-        }
+            let qa_callback: Option<ArcStr> = app.attr(node_key, ON_QUICK_ACTION)?;
+            if let Some(qa_callback) = qa_callback {
+                let callback = app.callbacks.get(&qa_callback).unwrap();
+                callback(app, node_key).map(|_| true)
+            } else {
+                Ok(false)
+            }
+        },
+        UserInputEvent::Drag => drag(app, m, node_key),
+        UserInputEvent::DragEnd => drag_end(app, m, node_key),
+        UserInputEvent::FocusLoss => {
+            let storage = storage(&mut app.mutators, m);
+            if storage.drag.map(|(dragged, _)| dragged) == Some(node_key) {
+                storage.drag = None;
+            }
 
-        app.view[node_key].config.set_dirty(true);
-        scroll(app, node_key, axis, candidate);
-
-        Ok(true)
-    } else if let UserInputEvent::QuickAction1 = event {
-        let qa_callback: Option<ArcStr> = app.attr(node_key, ON_QUICK_ACTION)?;
-        if let Some(qa_callback) = qa_callback {
-            let callback = app.callbacks.get(&qa_callback).unwrap();
-            callback(app, node_key).map(|_| true)
-        } else {
             Ok(false)
+        },
+        _ => Ok(false),
+    }
+}
+
+/// Scrolls on the length axis of the container; `axis` restricts the
+/// event to containers which scroll on that axis. Vertical wheels
+/// also scroll horizontal containers.
+fn wheel(app: &mut Application, m: MutatorIndex, node_key: NodeKey, axis: Option<Axis>, wheel_delta: SignedPixels) -> Result<bool, Error> {
+    let config = app.view[node_key].config;
+    let length_axis = config.get_length_axis();
+
+    if app.view[node_key].overflow != Overflow::Scroll || axis.unwrap_or(length_axis) != length_axis {
+        return Ok(false);
+    }
+
+    storage(&mut app.mutators, m).flings.retain(|fling| fling.node_key != node_key);
+    scroll_by(app, node_key, wheel_delta)
+}
+
+fn contains(rect: (Position, Size), p: Position) -> bool {
+    let max = rect.0.add_size(rect.1);
+    rect.0.x <= p.x && p.x < max.x && rect.0.y <= p.y && p.y < max.y
+}
+
+/// Drags the thumb if the drag started on the scrollbar, or the content otherwise
+fn drag(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<bool, Error> {
+    let scrollbar = match get_scrollbar(app, node_key) {
+        Some(scrollbar) => scrollbar,
+        None => return Ok(false),
+    };
+
+    let now = app.age();
+    let pointer = app.get_focus_coords();
+    let p = pointer.get_for_axis(scrollbar.axis);
+
+    let storage = storage(&mut app.mutators, m);
+    let mode = match storage.drag {
+        Some((dragged, mode)) if dragged == node_key => mode,
+        _ => {
+            let mode = match contains(scrollbar.track, pointer) {
+                true => DragMode::Thumb,
+                false => DragMode::Content {
+                    pointer: p,
+                    sample: (p, now),
+                    velocity: 0.0,
+                },
+            };
+
+            storage.flings.retain(|fling| fling.node_key != node_key);
+
+            // keep receiving Drag events when the pointer leaves the container
+            app.set_focused_node(node_key)?;
+            set_drag_mode(app, m, node_key, mode);
+            mode
+        },
+    };
+
+    match mode {
+        DragMode::Thumb => drag_thumb(app, node_key, &scrollbar)?,
+        DragMode::Content { pointer, sample, velocity } => {
+            scroll_by(app, node_key, p - pointer)?;
+
+            // events between two frames share the same age
+            let (sample, velocity) = match now > sample.1 {
+                true => {
+                    let dt = (now - sample.1).as_secs_f32();
+                    let instant = (p - sample.0).to_num::<f32>() / dt;
+                    ((p, now), VELOCITY_SMOOTHING * instant + (1.0 - VELOCITY_SMOOTHING) * velocity)
+                },
+                false => (sample, velocity),
+            };
+
+            set_drag_mode(app, m, node_key, DragMode::Content { pointer: p, sample, velocity });
+        },
+    }
+
+    Ok(true)
+}
+
+fn set_drag_mode(app: &mut Application, m: MutatorIndex, node_key: NodeKey, mode: DragMode) {
+    storage(&mut app.mutators, m).drag = Some((node_key, mode));
+}
+
+/// Starts a fling if the content was moving when it was released
+fn drag_end(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<bool, Error> {
+    let age = app.age();
+    let storage = storage(&mut app.mutators, m);
+    let mode = match storage.drag {
+        Some((dragged, mode)) if dragged == node_key => mode,
+        _ => return Ok(false),
+    };
+
+    storage.drag = None;
+
+    if let DragMode::Content { sample, velocity, .. } = mode {
+        // the pointer might have stood still before being released
+        let recent = age.saturating_sub(sample.1) <= FLING_WINDOW;
+
+        if recent && Float::abs(velocity) > MIN_FLING_VELOCITY {
+            // flings of nodes which disappeared in a reload
+            storage.flings.retain(|fling| app.view.get(fling.node_key).is_some());
+            storage.flings.push(Fling {
+                node_key,
+                velocity,
+                last_step: age,
+            });
+
+            app.animate(node_key, fling_step);
         }
-    } else {
-        Ok(false)
     }
+
+    if app.get_explicit_focus() == Some(node_key) {
+        app.clear_focused_node()?;
+    }
+
+    Ok(true)
+}
+
+/// Scrolls a flung container, which decelerates until it stops or reaches an end
+fn fling_step(app: &mut Application, node_key: NodeKey, _elapsed: Duration) -> Result<bool, Error> {
+    let m = app.view[node_key].factory.get().ok_or_else(|| error!())?;
+    let age = app.age();
+
+    let flings = &mut storage(&mut app.mutators, m).flings;
+    let fling = match flings.iter_mut().find(|fling| fling.node_key == node_key) {
+        Some(fling) => fling,
+        // stopped by a wheel or drag event, or the view was reloaded
+        None => return Ok(false),
+    };
+
+    let dt = age.saturating_sub(fling.last_step).as_secs_f32();
+    let distance = fling.velocity * dt;
+    fling.last_step = age;
+    fling.velocity *= Float::powf(FLING_DECAY, dt);
+    let velocity = fling.velocity;
+
+    let moved = dt == 0.0 || scroll_by(app, node_key, SignedPixels::from_num(distance))?;
+    let running = moved && Float::abs(velocity) > MIN_FLING_VELOCITY;
+
+    if !running {
+        storage(&mut app.mutators, m).flings.retain(|fling| fling.node_key != node_key);
+    }
+
+    Ok(running)
+}
+
+/// Scrolls so that the middle of the thumb is under the pointer
+fn drag_thumb(app: &mut Application, node_key: NodeKey, scrollbar: &Scrollbar) -> Result<(), Error> {
+    let axis = scrollbar.axis;
+    let pointer = app.get_focus_coords().get_for_axis(axis).to_num::<f32>();
+    let track_start = scrollbar.track.0.get_for_axis(axis).to_num::<f32>();
+    let track_length = scrollbar.track.1.get_for_axis(axis).to_num::<f32>();
+    let thumb_length = scrollbar.thumb.1.get_for_axis(axis).to_num::<f32>();
+
+    let free_length = track_length - thumb_length;
+    if free_length <= 0.0 {
+        return Ok(());
+    }
+
+    let progress = ((pointer - track_start - thumb_length / 2.0) / free_length).clamp(0.0, 1.0);
+    let new_scroll = SignedPixels::from_num(progress * scrollbar.max_scroll.to_num::<f32>());
+    scroll_by(app, node_key, scrollbar.scroll - new_scroll).map(|_| ())
 }
 
+/// Items instantiated on each side of the viewport of virtual lists
+const OVERSCAN: usize = 3;
+
+/// Assumed length of items before any of them is measured
+const DEFAULT_ITEM_LENGTH: f32 = 32.0;
+
+const VISIBLE: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("visible"));
+
+/// Weight of the latest sample in the velocity of dragged content
+const VELOCITY_SMOOTHING: f32 = 0.8;
+
+/// Content is flung if it moved during this window before being released
+const FLING_WINDOW: Duration = Duration::from_millis(100);
+
+/// Fraction of the velocity of a fling which remains after one second
+const FLING_DECAY: f32 = 0.05;
+
+/// Flings stop below this velocity, in pixels per second
+const MIN_FLING_VELOCITY: f32 = 20.0;
+
 // common attributes
 const FOR:             usize = 0;
 const IN:              usize = 1;
@@ -289,12 +959,19 @@ const BORDER_RADIUS:   usize = 5;
 const GAP:             usize = 6;
 const ON_QUICK_ACTION: usize = 7;
 const HOVER:           usize = 8;
+// tooltip (9) is handled by the tooltip mutator
+const TRANSITION:      usize = 10;
+const OVERFLOW:        usize = 11;
+const VIRTUAL:         usize = 12;
+const ITEM_LENGTH:     usize = 13;
+const WHERE:           usize = 14;
+const SORT_BY:         usize = 15;
 
 // specific
-const ROW:             usize = 9;
-const LENGTH:          usize = 9;
-const RATIO:           usize = 9;
-const WEIGHT:          usize = 9;
+const ROW:             usize = 16;
+const LENGTH:          usize = 16;
+const RATIO:           usize = 16;
+const WEIGHT:          usize = 16;
 
 macro_rules! container {
     ($name:ident, $tag:literal $(, $arg:expr)?) => {
@@ -312,14 +989,23 @@ macro_rules! container {
                     ("gap", AttributeValueType::Pixels, Some(crate::ZERO_ARCSTR)),
                     ("on-quick-action", AttributeValueType::OptOther, None),
                     ("hover", AttributeValueType::OptOther, None),
+                    ("tooltip", AttributeValueType::OptOther, None),
+                    ("transition", AttributeValueType::OptOther, None),
+                    ("overflow", AttributeValueType::Other, Some(VISIBLE)),
+                    ("virtual", AttributeValueType::Boolean, Some(FALSE_STR)),
+                    ("item-length", AttributeValueType::OptPixels, None),
+                    ("where", AttributeValueType::OptOther, None),
+                    ("sort-by", AttributeValueType::OptOther, None),
                     $($arg)*
                 ],
                 accepts_children: true,
+                text_content: None,
             }),
             handlers: Handlers {
                 initializer,
                 populator,
                 resizer,
+                scroller,
                 user_input_handler,
                 ..DEFAULT_HANDLERS
             },
@@ -345,3 +1031,19 @@ pub const CONTAINERS: [Mutator; 10] = [
     HC_WRAP_MUTATOR, VC_WRAP_MUTATOR,
     HC_REM_MUTATOR, VC_REM_MUTATOR,
 ];
+
+#[test]
+fn measures_prefix_sums() {
+    let mut measures = Measures::new(10);
+    measures.add(2, 30.0, 1);
+    measures.add(7, 10.0, 1);
+    measures.add(2, -5.0, 0);
+
+    assert_eq!(measures.prefix(0), (0.0, 0));
+    assert_eq!(measures.prefix(3), (25.0, 1));
+    assert_eq!(measures.prefix(10), (35.0, 2));
+    assert_eq!(measures.prefix(20), (35.0, 2));
+
+    assert_eq!(search(10, |i| measures.prefix(i).1 == 2), 8);
+    assert_eq!(search(10, |_| false), 10);
+}
@@ -1,18 +1,24 @@
-use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType, parse_xml_tree};
-use crate::{Box, HashMap, ArcStr, Error, ro_string};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, XmlTags, AttributeValueType, Templates, parse_xml_tree};
+use crate::{Box, Vec, HashMap, ArcStr, Error, error, ro_string};
 use crate::core::app::Application;
 use crate::core::event::{Handlers, DEFAULT_HANDLERS};
 use crate::core::node::{NodeKey, Mutator, MutatorIndex, get_storage};
+use crate::core::state::{Namespace, root_ns};
 use oakwood::NodeKey as _;
 
-const FILE: usize = 0;
+const FILE:  usize = 0;
+const STATE: usize = 1;
 
 pub const IMPORT_MUTATOR: Mutator = Mutator {
     name: ro_string!("ImportMutator"),
     xml_params: Some(XmlTagParameters {
         tag_name: ro_string!("import"),
-        attr_set: &[ ("file", AttributeValueType::Other, None) ],
+        attr_set: &[
+            ("file", AttributeValueType::Other, None),
+            ("state", AttributeValueType::OptOther, None),
+        ],
         accepts_children: false,
+        text_content: None,
     }),
     handlers: Handlers {
         initializer,
@@ -24,39 +30,57 @@ pub const IMPORT_MUTATOR: Mutator = Mutator {
     storage: None,
 };
 
-type SubLayouts = HashMap<ArcStr, XmlNodeKey>;
+struct ImportStorage {
+    sub_layouts: HashMap<ArcStr, XmlNodeKey>,
+    /// Templates defined in all parsed layouts
+    templates: Templates,
+}
 
 fn initializer(app: &mut Application, m: MutatorIndex) -> Result<(), Error> {
     let storage = &mut app.mutators[usize::from(m)].storage;
     assert!(storage.is_none());
 
-    *storage = Some(Box::new(SubLayouts::new()));
+    *storage = Some(Box::new(ImportStorage {
+        sub_layouts: HashMap::new(),
+        templates: Vec::new(),
+    }));
 
     Ok(())
 }
 
-fn populator(app: &mut Application, _m: MutatorIndex, node_key: NodeKey, _xml_node_key: XmlNodeKey) -> Result<(), Error> {
+/// `state="ns:path"` re-roots `root:` lookups of the imported layout at `path`
+fn populator(app: &mut Application, _m: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
     let layout_asset = app.attr(node_key, FILE)?;
+    let state: Option<ArcStr> = app.attr(node_key, STATE)?;
+
+    if let Some(state) = state {
+        let (ns_name, ns_path) = state.split_once(':')
+            .ok_or_else(|| error!("{}: <import state=...> - missing colon in \"state\"", app.xml_location(xml_node_key)))?;
+
+        let path = app.resolve(node_key, ns_name, ns_path)?;
+        app.add_namespace(node_key, Namespace { path, ..root_ns() });
+    }
+
     app.request(&layout_asset, node_key, true)
 }
 
 fn parser(app: &mut Application, m: MutatorIndex, _node_key: NodeKey, asset: &ArcStr, bytes: Box<[u8]>) -> Result<(), Error> {
-    let mut xml_tags = HashMap::<str, (&XmlTagParameters, MutatorIndex)>::new();
-    for i in 0..app.mutators.len() {
-        if let Some(params) = &app.mutators[i].xml_params {
-            xml_tags.insert_ref(&params.tag_name.clone(), (params, i.into()));
-        }
-    }
+    let storage: &mut ImportStorage = get_storage(&mut app.mutators, m).unwrap();
+    let mut templates = core::mem::take(&mut storage.templates);
+    let file = app.add_source_file(asset);
 
     let replacement = parse_xml_tree(
-        xml_tags,
-        &app.mutators,
+        &XmlTags::new(&app.mutators, &app.libraries),
         &mut app.xml_tree,
+        &mut templates,
+        file,
+        asset,
         &bytes,
-    )?;
+    );
 
-    let storage: &mut SubLayouts = get_storage(&mut app.mutators, m).unwrap();
-    storage.insert(asset.clone(), replacement);
+    let storage: &mut ImportStorage = get_storage(&mut app.mutators, m).unwrap();
+    storage.templates = templates;
+    storage.sub_layouts.insert(asset.clone(), replacement?);
 
     Ok(())
 }
@@ -65,8 +89,8 @@ fn finalizer(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Resul
     let file: ArcStr = app.attr(node_key, FILE)?;
 
     let replacement = {
-        let storage: &mut SubLayouts = get_storage(&mut app.mutators, m).unwrap();
-        *storage.get(&file).unwrap()
+        let storage: &mut ImportStorage = get_storage(&mut app.mutators, m).unwrap();
+        *storage.sub_layouts.get(&file).unwrap()
     };
 
     app.view.reset(node_key);
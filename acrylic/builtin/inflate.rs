@@ -19,6 +19,7 @@ pub const INFLATE_MUTATOR: Mutator = Mutator {
         tag_name: ro_string!("inflate"),
         attr_set: &[],
         accepts_children: false,
+        text_content: None,
     }),
     handlers: Handlers {
         populator,
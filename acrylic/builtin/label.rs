@@ -1,16 +1,37 @@
 use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
 use crate::core::event::{Handlers, UserInputEvent, DEFAULT_HANDLERS};
-use crate::core::visual::{aspect_ratio, Ratio, LayoutMode};
+use crate::core::visual::{aspect_ratio, Ratio, Pixels, LayoutMode, Axis, Size};
 use crate::core::node::{NodeKey, Mutator, MutatorIndex};
 use crate::core::glyph::{get_font, load_font_bytes};
-use crate::core::text_edit::text_edit;
+use crate::core::text_edit::{text_edit, initializer as text_edit_initializer, display_text, is_editable, LineBreaks, TextAttributes, input_style};
 use crate::core::app::Application;
-use crate::{DEFAULT_FONT_NAME, FALSE_STR, Error, ArcStr, ro_string, Box};
+use crate::{DEFAULT_FONT_NAME, FALSE_STR, TEXT_STR, Error, error, ArcStr, String, ro_string, Box};
+use core::mem::ManuallyDrop;
 
 const TEXT: usize = 0;
 const FONT: usize = 1;
 const EDITABLE: usize = 2;
 const RATIO: usize = 3;
+const OVERFLOW: usize = 4;
+const MIN_SIZE: usize = 5;
+const MAX_SIZE: usize = 6;
+const PLACEHOLDER: usize = 7;
+const MASK: usize = 8;
+const INPUT: usize = 9;
+const PATTERN: usize = 10;
+
+const TEXT_ATTRIBUTES: TextAttributes = TextAttributes {
+    editable: EDITABLE,
+    font: FONT,
+    text: TEXT,
+    placeholder: PLACEHOLDER,
+    mask: Some(MASK),
+    input: INPUT,
+    pattern: PATTERN,
+};
+
+const SHRINK: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("shrink"));
+const ELLIPSIS: &str = "\u{2026}";
 
 pub const LABEL_MUTATOR: Mutator = Mutator {
     name: ro_string!("LabelMutator"),
@@ -19,12 +40,21 @@ pub const LABEL_MUTATOR: Mutator = Mutator {
         attr_set: &[
             ("text", AttributeValueType::Other, None),
             ("font", AttributeValueType::Other, Some(DEFAULT_FONT_NAME)),
-            ("editable", AttributeValueType::Other, Some(FALSE_STR)),
+            ("editable", AttributeValueType::Boolean, Some(FALSE_STR)),
             ("weight", AttributeValueType::OptRatio, None),
+            ("overflow", AttributeValueType::Other, Some(SHRINK)),
+            ("min-size", AttributeValueType::OptPixels, None),
+            ("max-size", AttributeValueType::OptPixels, None),
+            ("placeholder", AttributeValueType::OptOther, None),
+            ("mask", AttributeValueType::OptOther, None),
+            ("input", AttributeValueType::Other, Some(TEXT_STR)),
+            ("pattern", AttributeValueType::OptOther, None),
         ],
         accepts_children: false,
+        text_content: Some(TEXT),
     }),
     handlers: Handlers {
+        initializer: text_edit_initializer,
         populator,
         parser,
         finalizer,
@@ -35,11 +65,130 @@ pub const LABEL_MUTATOR: Mutator = Mutator {
     storage: None,
 };
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Overflow {
+    Shrink,
+    Ellipsis,
+    Clip,
+}
+
+fn overflow(app: &mut Application, node_key: NodeKey) -> Result<Overflow, Error> {
+    let overflow: ArcStr = app.attr(node_key, OVERFLOW)?;
+    match &*overflow {
+        "shrink" => Ok(Overflow::Shrink),
+        "ellipsis" => Ok(Overflow::Ellipsis),
+        "clip" => Ok(Overflow::Clip),
+        other => Err(error!("{}: Invalid label overflow: {:?} (expected shrink, ellipsis or clip)", app.node_location(node_key), other)),
+    }
+}
+
+fn parent_axis(app: &Application, node_key: NodeKey) -> Axis {
+    match app.view.parent(node_key) {
+        Some(parent) => app.view[parent].config.get_content_axis(),
+        None => Axis::Horizontal,
+    }
+}
+
+/// `min-size` and `max-size` attributes
+fn size_bounds(app: &mut Application, node_key: NodeKey) -> Result<(Option<Pixels>, Option<Pixels>), Error> {
+    Ok((app.attr(node_key, MIN_SIZE)?, app.attr(node_key, MAX_SIZE)?))
+}
+
+/// Clamps a font size between `min-size` and `max-size`
+fn clamp_size(size: Pixels, (min_size, max_size): (Option<Pixels>, Option<Pixels>)) -> Pixels {
+    let size = match max_size {
+        Some(max_size) => size.min(max_size),
+        None => size,
+    };
+
+    match min_size {
+        Some(min_size) => size.max(min_size),
+        None => size,
+    }
+}
+
+/// The height of the node, clamped between `min-size` and `max-size`
+fn font_size(app: &mut Application, node_key: NodeKey) -> Result<usize, Error> {
+    let height = app.view[node_key].size.h;
+    let bounds = size_bounds(app, node_key)?;
+    Ok(clamp_size(height, bounds).round().to_num())
+}
+
+/// Layout mode of a label whose text has an aspect ratio of `aspect`
+///
+/// It's computed from the current `size` of the label on each pass, so
+/// that the label gets its natural aspect ratio back when its font size
+/// isn't clamped anymore.
+fn layout_mode(
+    ratio: Option<Ratio>,
+    overflow: Overflow,
+    parent_axis: Axis,
+    aspect: Ratio,
+    size: Size,
+    bounds: (Option<Pixels>, Option<Pixels>),
+) -> LayoutMode {
+    match (ratio, overflow, parent_axis) {
+        (Some(ratio), _, _) => LayoutMode::Remaining(ratio),
+        // the height of the label is the one of its container
+        (None, Overflow::Shrink, Axis::Horizontal) => match clamp_size(size.h, bounds) {
+            font_size if font_size == size.h => LayoutMode::AspectRatio(aspect),
+            font_size => LayoutMode::Fixed(font_size.saturating_mul(aspect.to_num()).round()),
+        },
+        (None, _, Axis::Horizontal) => LayoutMode::Remaining(Ratio::ONE),
+        // the height of the label depends on the width of its container
+        (None, _, Axis::Vertical) => {
+            let natural = size.w.checked_div(aspect.to_num()).unwrap_or(Pixels::ZERO);
+            match clamp_size(natural, bounds) {
+                font_size if font_size == natural => LayoutMode::AspectRatio(aspect),
+                font_size => LayoutMode::Fixed(font_size.round()),
+            }
+        },
+    }
+}
+
+/// Whether switching from `current` to `next` would change the layout
+fn layout_changed(current: LayoutMode, next: LayoutMode) -> bool {
+    match (current, next) {
+        (LayoutMode::Fixed(a), LayoutMode::Fixed(b)) => a.dist(b) >= Pixels::ONE,
+        // aspect ratios lose some precision in the node config
+        (LayoutMode::AspectRatio(a), LayoutMode::AspectRatio(b)) => a.dist(b) > Ratio::from_num(0.001),
+        (current, next) => current != next,
+    }
+}
+
+/// Sets the layout mode of the label, returning true if it changed
+fn update_layout(app: &mut Application, node_key: NodeKey, text: &str, overflow: Overflow) -> Result<bool, Error> {
+    let ratio: Option<Ratio> = app.attr(node_key, RATIO)?;
+    let font_file: ArcStr = app.attr(node_key, FONT)?;
+    let bounds = size_bounds(app, node_key)?;
+    let font_size = 100;
+
+    let font = get_font(&mut app.mutators, &font_file).unwrap();
+    let width = match text.len() {
+        // a square, so that the label can be clicked
+        0 => font_size,
+        _ => font.quick_width(text, font_size),
+    };
+
+    let aspect = aspect_ratio(width, font_size);
+    let size = app.view[node_key].size;
+    let next = layout_mode(ratio, overflow, parent_axis(app, node_key), aspect, size, bounds);
+    let changed = layout_changed(app.view[node_key].config.get_layout_mode(), next);
+
+    if changed {
+        app.view[node_key].config.set_layout_mode(next);
+        app.invalidate_layout();
+    }
+
+    Ok(changed)
+}
+
 fn populator(app: &mut Application, _m: MutatorIndex, node_key: NodeKey, _xml_node_key: XmlNodeKey) -> Result<(), Error> {
-    let text:      ArcStr = app.attr(node_key, TEXT)?;
+    let (text, _) = display_text(app, node_key, TEXT_ATTRIBUTES)?;
     let font_file: ArcStr = app.attr(node_key, FONT)?;
 
-    match text.len() > 0 {
+    // empty editable labels still need a caret and a clickable area
+    match !text.is_empty() || is_editable(app, node_key, TEXT_ATTRIBUTES)? {
         true => app.request(&font_file, node_key, true),
         false => Ok(()),
     }
@@ -50,48 +199,81 @@ fn parser(app: &mut Application, _m: MutatorIndex, _node_key: NodeKey, asset: &A
 }
 
 fn finalizer(app: &mut Application, _m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
-    let ratio: Option<Ratio> = app.attr(node_key, RATIO)?;
-    let font_file: ArcStr = app.attr(node_key, FONT)?;
-    let text: ArcStr = app.attr(node_key, TEXT)?;
+    let (text, _) = display_text(app, node_key, TEXT_ATTRIBUTES)?;
+    let overflow = overflow(app, node_key)?;
 
-    if text.len() > 0 {
-        let font_size = 100;
+    if !text.is_empty() || is_editable(app, node_key, TEXT_ATTRIBUTES)? {
+        update_layout(app, node_key, &text, overflow)?;
+    }
 
-        let font = get_font(&mut app.mutators, &font_file).unwrap();
-        let width = font.quick_width(&text, font_size);
+    Ok(())
+}
 
-        let layout_mode = match ratio {
-            Some(ratio) => LayoutMode::Remaining(ratio),
-            None => LayoutMode::AspectRatio(aspect_ratio(width, font_size)),
-        };
+/// Truncates `text` so that it fits in `max_width` with a trailing ellipsis
+fn ellipsize(app: &mut Application, font_file: &ArcStr, text: &str, font_size: usize, max_width: usize) -> String {
+    let font = get_font(&mut app.mutators, font_file).unwrap();
+    let mut candidate = String::from(text);
 
-        app.view[node_key].config.set_layout_mode(layout_mode);
+    if font.quick_width(text, font_size) > max_width {
+        while candidate.pop().is_some() {
+            let trimmed = candidate.trim_end().len();
+            candidate.truncate(trimmed);
+            candidate.push_str(ELLIPSIS);
 
-        app.invalidate_layout();
+            if font.quick_width(&candidate, font_size) <= max_width {
+                break;
+            }
+
+            candidate.truncate(trimmed);
+        }
     }
 
-    Ok(())
+    candidate
 }
 
 fn resizer(app: &mut Application, _m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
-    let text:      ArcStr = app.attr(node_key, TEXT)?;
+    let (text, is_placeholder) = display_text(app, node_key, TEXT_ATTRIBUTES)?;
     let font_file: ArcStr = app.attr(node_key, FONT)?;
+    let overflow = overflow(app, node_key)?;
 
-    let inherited_style = app.get_inherited_style(node_key)?;
-    let cursors = match Some(node_key) == app.get_explicit_focus() {
-        true => Some((0, app.text_cursors.as_slice())),
-        false => None,
-    };
+    if text.is_empty() && !is_editable(app, node_key, TEXT_ATTRIBUTES)? {
+        return Ok(());
+    }
+
+    // font sizes are clamped once the size of the container is known
+    if update_layout(app, node_key, &text, overflow)? {
+        return Ok(());
+    }
+
+    let style = input_style(app, node_key, TEXT_ATTRIBUTES)?;
+    let is_focused = Some(node_key) == app.get_explicit_focus();
 
-    if text.len() > 0 && !app.debug.skip_glyph_rendering {
-        let color = Some(inherited_style.foreground);
-        let font_size = app.view[node_key].size.h.round().to_num();
+    if !app.debug.skip_glyph_rendering {
+        let font_size = font_size(app, node_key)?;
+        let height = app.view[node_key].size.h.round().to_num();
+        let width = app.view[node_key].size.w.to_num();
+
+        // the edited text must be displayed entirely
+        let text = match (overflow, is_focused && !is_placeholder) {
+            (Overflow::Ellipsis, false) => ellipsize(app, &font_file, &text, font_size, width).into(),
+            _ => text,
+        };
+
+        let cursors = match is_focused {
+            true => Some((0, app.text_cursors.as_slice())),
+            false => None,
+        };
+
+        let color = Some(match is_placeholder {
+            true => style.dimmed_foreground(),
+            false => style.foreground,
+        });
         app.view[node_key].config.set_dirty(true);
         app.view[node_key].foreground = {
             let font = get_font(&mut app.mutators, &font_file).unwrap();
             let mut renderer = font.renderer(color, cursors, font_size);
             renderer.write(&text);
-            renderer.texture()
+            renderer.texture_with_height(height)
         };
     }
 
@@ -105,6 +287,23 @@ fn user_input_handler(
     _target: NodeKey,
     event: &UserInputEvent,
 ) -> Result<bool, Error> {
-    let font_size = app.view[node_key].size.h.round().to_num();
-    text_edit(false, app, node_key, event, EDITABLE, FONT, font_size, TEXT)
+    let font_size = Pixels::from_num(font_size(app, node_key)?);
+    text_edit(LineBreaks::Never, app, node_key, event, TEXT_ATTRIBUTES, font_size)
+}
+
+#[test]
+fn min_size_in_narrow_containers() {
+    let bounds = (Some(Pixels::from_num(16)), Some(Pixels::from_num(40)));
+    let aspect = aspect_ratio(500, 100);
+    let vertical = |width| layout_mode(None, Overflow::Shrink, Axis::Vertical, aspect, Size::new(Pixels::from_num(width), Pixels::ZERO), bounds);
+
+    // the natural height of the text would be 8 pixels
+    assert_eq!(vertical(40), LayoutMode::Fixed(Pixels::from_num(16)));
+    // the label gets its aspect ratio back when its container widens
+    assert_eq!(vertical(100), LayoutMode::AspectRatio(aspect));
+    assert_eq!(vertical(400), LayoutMode::Fixed(Pixels::from_num(40)));
+
+    let horizontal = |height| layout_mode(None, Overflow::Shrink, Axis::Horizontal, aspect, Size::new(Pixels::ZERO, Pixels::from_num(height)), bounds);
+    assert_eq!(horizontal(10), LayoutMode::Fixed(Pixels::from_num(80)));
+    assert_eq!(horizontal(20), LayoutMode::AspectRatio(aspect));
 }
@@ -6,12 +6,57 @@
 //!
 //! - `for`, `in`: see Iterating Containers
 //! - `style`: name of the style to apply to this container
-//! - `hover`: style override when the container is hovered by a cursor
+//! - `hover`: style override when the container is hovered by a cursor; its background
+//!   is only used if the container has a `style`
 //! - `margin`: node margin, as a number of pixels
 //! - `border-width`: node border width, as a number of pixels
 //! - `border-radius`: node border radius, as a number of pixels
 //! - `gap`: gap between children, as a number of pixels
 //! - `on-quick-action`: callback to call when the container receives a QuickAction1 user event
+//! - `tooltip`: text of a tooltip, see Tooltips
+//! - `transition`: duration & easing curve of transitions, see Transitions
+//! - `overflow`: `visible` (default), `clip` or `scroll`, see Scrolling
+//! - `where`, `sort-by`: see Iterating Containers
+//! - `virtual`, `item-length`: see Virtual Lists
+//!
+//! ## Scrolling
+//!
+//! The `overflow` attribute tells what happens to children which
+//! don't fit in a container:
+//!
+//! - `visible`: they are painted outside of the container,
+//! - `clip`: they are cut at the edges of the container,
+//! - `scroll`: they are cut, and the container can be scrolled on
+//!   its length axis with wheels (`WheelX` only scrolls horizontal
+//!   containers) or by dragging its scrollbar.
+//!
+//! The thumb of the scrollbar has the outline color of the container's style.
+//! Nodes can be brought into view with [`scroll_into_view`]; focused
+//! nodes are scrolled into view automatically.
+//!
+//! Dragging the content (e.g. on touch screens) scrolls it too; if it was
+//! still moving when released, it keeps scrolling and slows down progressively.
+//! Scroll offsets are kept when the view is reloaded.
+//!
+//! [`scroll_into_view`]: crate::core::app::Application::scroll_into_view
+//!
+//! ## Transitions
+//!
+//! With a `transition` attribute, a container is moved & resized
+//! progressively when the layout changes, and its background & border
+//! colors change progressively when it's hovered:
+//!
+//! ```xml
+//! <h-rem style="neutral-inert" hover="neutral-focus" transition="200ms ease-out">
+//!     <label text="Hover me" />
+//! </h-rem>
+//! ```
+//!
+//! The duration is in milliseconds (`ms`) or seconds (`s`). The optional
+//! easing curve is one of `linear`, `ease` (default), `ease-in`, `ease-out`
+//! and `ease-in-out`. When the target changes before the end of a transition,
+//! a new one starts from the displayed values. Reloading the view (after
+//! a state change) cancels running transitions.
 //!
 //! ## Iterating Containers
 //!
@@ -35,6 +80,42 @@
 //! field of each object in the list at `club` / `members` in the root
 //! (main) JSON state namespace.
 //!
+//! Containers can also iterate over the entries of a JSON object. In
+//! both cases, `@index` is the position of the current item and `@key`
+//! its key (for arrays, this is the position too). Namespaces of outer
+//! iterating containers remain available to nested ones:
+//!
+//! ```xml
+//! <v-wrap for="team" in="root:teams">
+//!     <v-wrap for="member" in="team:members">
+//!         <h-fixed length="40">
+//!             <label member:text="@index" />
+//!             <label member:text="name" />
+//!             <label team:text="@key" />
+//!         </h-fixed>
+//!     </v-wrap>
+//! </v-wrap>
+//! ```
+//!
+//! Here, `root:teams` is an object mapping team names to team objects.
+//!
+//! All XML children of an iterating container are repeated for each item.
+//! The `where` attribute only keeps the items which pass a condition, and
+//! `sort-by` orders them by the value at a path (descending if the path
+//! is prefixed with `-`). Both are evaluated against each item:
+//!
+//! ```xml
+//! <v-wrap for="message" in="root:inbox" where="!read" sort-by="-date">
+//!     <label message:text="subject" />
+//!     <label message:text="body" />
+//! </v-wrap>
+//! ```
+//!
+//! Conditions are either a path (the value must be truthy: not `false`,
+//! `null`, `0`, an empty string or an empty array / object), a path prefixed
+//! with `!` (the value must be falsy), `path == literal` or `path != literal`.
+//! `@index` & `@key` still refer to the position of the item in the JSON state.
+//!
 //! Technically, the container will produce as many children nodes as
 //! required by the list and subscribe to that list. Then, when the
 //! children nodes are initialized, they will subscribe to individual
@@ -43,6 +124,27 @@
 //! the subscribed nodes will either be replaced by new ones or updated
 //! accordingly.
 //!
+//! ## Virtual Lists
+//!
+//! With `virtual="true"`, an iterating container only creates the
+//! children which are in its viewport, plus a few on each side. While
+//! it's scrolled, children which leave the viewport are recycled for the
+//! items which enter it: only their nodes which depend on the item are
+//! re-created, so that very long lists stay cheap to lay out & paint:
+//!
+//! ```xml
+//! <v-rem for="entry" in="root:log" overflow="scroll" virtual="true" item-length="24">
+//!     <h-fixed length="24">
+//!         <label entry:text="message" />
+//!     </h-fixed>
+//! </v-rem>
+//! ```
+//!
+//! `item-length` is the length of each child, in pixels. Without it,
+//! children are measured once created; the ones which never were are
+//! assumed to have the average measured length. Virtual containers
+//! must have `overflow="scroll"` and cannot be chunks containers.
+//!
 //! ## List of tags
 //!
 //! ### Wrapping Containers
@@ -83,21 +185,238 @@
 //! The `<inflate>` tag will result in an empty/transparent node taking all
 //! remaining space.
 //!
+//! # Buttons: `<button>`
+//!
+//! An horizontal container which can be activated with a QuickAction1
+//! user event or by pressing Enter while it has the focus. It usually
+//! contains a label:
+//!
+//! ```xml
+//! <button on-quick-action="save" root:disabled="form.pristine">
+//!     <label text="Save" />
+//! </button>
+//! ```
+//!
+//! Special Attributes:
+//! - `on-quick-action`: callback to call when the button is activated
+//! - `disabled`: if `true`, the button cannot be activated; defaults to `false`
+//! - `style`: defaults to `neutral-inert`
+//! - `hover`: style when the button is hovered; defaults to `neutral-focus`
+//! - `pressed`: style for a short time after an activation (by the
+//!   pointer or the keyboard); defaults to `incite-focus`
+//! - `disabled-style`: defaults to `default`
+//! - `margin`, `border-width`, `border-radius`, `gap`: see containers
+//! - `length`: optional fixed length, in pixels; buttons wrap their content by default
+//! - `tooltip`: text of a tooltip, see Tooltips
+//!
+//! A focus ring is drawn around the button when it has the focus.
+//!
+//! # Boolean inputs: `<checkbox>` & `<toggle>`
+//!
+//! A checkbox (square) and a toggle switch (twice as wide as high),
+//! drawn with the `incite-inert` style, or `incite-focus` when checked.
+//! QuickAction1 flips their state.
+//!
+//! Special Attribute: `checked` (a boolean, no default); it must be a
+//! JSON state path for the widget to be interactive:
+//!
+//! ```xml
+//! <toggle root:checked="settings.dark-mode" />
+//! ```
+//!
+//! # Numeric input: `<slider>`
+//!
+//! An horizontal track with a thumb, filled up to the thumb. Clicking
+//! or dragging moves the thumb to the pointer; the wheel and arrow keys
+//! move it by one `step`.
+//!
+//! Special Attributes:
+//! - `value`: a number, which must be a JSON state path for the slider
+//!   to be interactive; no default
+//! - `min`, `max`: bounds of the value; default to 0 and 1
+//! - `step`: the value is rounded to `min` plus a multiple of `step`;
+//!   without it, the value is continuous and the wheel & arrow keys move
+//!   it by a twentieth of the range
+//! - `size`: height of the slider in vertical containers, in pixels;
+//!   defaults to 24. In horizontal containers, sliders take all remaining space.
+//!
+//! ```xml
+//! <slider root:value="player.volume" min="0" max="100" step="5" />
+//! ```
+//!
+//! # Tooltips: `<tooltip>`
+//!
+//! When the pointer rests on a node for a short delay, its tooltip
+//! is displayed in an overlay, near the pointer. It's hidden when
+//! the pointer leaves the node. Tooltips can be specified with the
+//! `tooltip` attribute of containers and buttons, or with a `<tooltip>`
+//! child, which can have children of its own:
+//!
+//! ```xml
+//! <button on-quick-action="open" tooltip="Open a document">
+//!     <label text="Open" />
+//! </button>
+//!
+//! <button on-quick-action="save">
+//!     <label text="Save" />
+//!     <tooltip text="Last saved:">
+//!         <label root:text="document.last-save" />
+//!     </tooltip>
+//! </button>
+//! ```
+//!
+//! `<tooltip>` children aren't displayed in the main view. If a node has
+//! both, the `<tooltip>` child is used.
+//!
+//! Special Attributes:
+//! - `text`: optional text, displayed before children
+//! - `font`: asset name for the font, defaults to `default`
+//! - `size`: font size and height of the content, defaults to 16 pixels
+//! - `style`: defaults to `menu-1`
+//!
+//! Platforms must feed the time to the application for the delay
+//! to be measured (see `Application::set_age`).
+//!
+//! # Popups, menus & dialogs: `<popup>`
+//!
+//! A `<popup>` displays its children in an overlay, above the main view,
+//! while its `open` attribute is `true`; in the main view, it takes no
+//! space. To open or close it, callbacks set the JSON state value of
+//! `open` and reload the view:
+//!
+//! ```xml
+//! <button on-quick-action="toggle-menu">
+//!     <label text="File" />
+//!     <popup root:open="menus.file">
+//!         <button on-quick-action="quit">
+//!             <label text="Quit" />
+//!         </button>
+//!     </popup>
+//! </button>
+//! ```
+//!
+//! The overlay is owned by the parent of the `<popup>`: JSON state
+//! namespaces and styles are inherited from it. Nodes under a modal
+//! popup cannot be hovered, clicked, scrolled or focused.
+//!
+//! Special Attributes:
+//! - `open`: a boolean, no default; it should be a JSON state path
+//! - `modal`: boolean, defaults to `false`
+//! - `anchor`: `below` the parent (default), at the `pointer`, or at the `center` of the window
+//! - `style`: defaults to `menu-1`
+//! - `gap`: gap between children, defaults to 6 pixels
+//!
+//! Popups are opened and closed right before the next frame, when
+//! `Application::set_age` is called.
+//!
+//! # Conditional rendering: `<if>`, `<else>`, `<switch>` & `<case>`
+//!
+//! These tags have exactly one XML child, which they display when
+//! their condition holds; otherwise they are left out of the layout.
+//!
+//! `<if>` has a `test` attribute: either `true` / `false`, or a JSON
+//! state lookup whose value must be truthy (not `false`, `null`, `0`,
+//! an empty string or an empty array / object). Lookups can also be
+//! negated or compared, with the syntax of the `where` attribute of
+//! iterating containers. An `<else>` right after an `<if>` displays
+//! its child when the condition of the `<if>` doesn't hold:
+//!
+//! ```xml
+//! <if root:test="user.logged-in">
+//!     <label root:text="user.name" />
+//! </if>
+//! <else>
+//!     <button on-quick-action="log-in">
+//!         <label text="Log in" />
+//!     </button>
+//! </else>
+//! ```
+//!
+//! `<switch>` compares its `value` attribute to the `value` of each of
+//! its `<case>` children, and displays the child of the first matching
+//! one. A `<case>` without `value` matches any value; cases may be empty.
+//!
+//! ```xml
+//! <switch root:value="player.state">
+//!     <case value="playing"><png file="pause.png" /></case>
+//!     <case value="loading" />
+//!     <case><png file="play.png" /></case>
+//! </switch>
+//! ```
+//!
+//! Conditions are evaluated again when the view is reloaded, i.e. after
+//! state changes.
+//!
+//! # Reusable components: `<template>` & `<slot>`
+//!
+//! A `<template>` defines a custom tag, which the XML parser expands
+//! wherever it's used. Its `name` attribute is the name of the new tag
+//! and `params` lists the attributes it accepts, separated by spaces.
+//!
+//! In the template, attributes of the form `param:name="p"` take the
+//! value of parameter `p` of each instance: either an immediate value
+//! or a JSON state lookup. Unspecified parameters leave the attribute
+//! to its default value. The XML children of an instance replace the
+//! `<slot />` of the template.
+//!
+//! ```xml
+//! <template name="card" params="title size">
+//!     <v-fixed param:length="size" style="menu-1">
+//!         <label param:text="title" />
+//!         <slot />
+//!     </v-fixed>
+//! </template>
+//!
+//! <card title="Settings" size="200">
+//!     <toggle root:checked="settings.dark-mode" />
+//! </card>
+//! <card root:title="user.name" size="100" />
+//! ```
+//!
+//! Like any other node, a `<template>` must be inside the root node of
+//! its file. Templates can be used after their definition in the same file, and
+//! in all files parsed afterwards (for instance via `<import>`).
+//! Defining a template with the name of another template replaces it.
+//!
 //! # Embedding another layout file: `<import>`
 //!
 //! Embeds another XML layout file into the current one.
 //!
 //! Special Attribute: `file` (name of the asset, no default)
 //!
-//! TODO: allow JSON state lookups from nodes in the asset to
-//! to start at some path in the JSON state of the app:
+//! Special Attribute: `state` (optional JSON state path)
+//!
+//! JSON state lookups from nodes in the imported layout can start
+//! at some path in the JSON state of the app:
 //!
 //! ```xml
 //! <import file="video-player.xml" state="root:videos.5643" />
 //! ```
 //!
-//! Here, when tags in `video-player.xml` refer to `root:something`, they'd be in
-//! fact referring to `root:videos.5643.something`.
+//! Here, when tags in `video-player.xml` refer to `root:something`, they're in
+//! fact referring to `root:videos.5643.something`. The path can also be in
+//! another namespace, so that a layout file can be reused for each item of
+//! an iterating container:
+//!
+//! ```xml
+//! <v-wrap for="video" in="root:videos">
+//!     <import file="video-player.xml" state="video:" />
+//! </v-wrap>
+//! ```
+//!
+//! # Tags of third-party crates
+//!
+//! Crates can provide a [`MutatorLibrary`](crate::core::node::MutatorLibrary),
+//! which is registered with [`Application::add_library`](crate::core::app::Application::add_library).
+//! Its tags are only found with the prefix of the library, in open
+//! and close tags:
+//!
+//! ```xml
+//! <charts:line root:points="stats.visits"></charts:line>
+//! ```
+//!
+//! Built-in tags and templates have no prefix. Prefixes of attributes
+//! are still JSON state namespaces.
 //!
 //! # Textual Nodes: Label & Paragraph
 //!
@@ -106,6 +425,92 @@
 //! - `text`: the text to be displayed; no default
 //! - `font`: asset name for the font, defaults to `default`
 //! - `editable`: whether or not to allow text edition; defaults to `false`
+//! - `placeholder`: text displayed in a dimmed color when `text` is empty; no default
+//! - `input`: what the text must be, one of `text` (default), `integer` or `number`
+//! - `pattern`: a regular expression which the whole text must match; no default
+//!
+//! Empty editable nodes keep a clickable area and display a caret when focused.
+//!
+//! The text of a node can be copied (Ctrl+C / Cmd+C on the web), even
+//! if it isn't editable: the focused node, or the hovered one, is copied.
+//!
+//! ### Text content
+//!
+//! The `text` attribute can also be given as the content of the tag
+//! (the same goes for `<tooltip>`). Whitespace sequences are collapsed
+//! to a single space, XML entities are decoded and CDATA sections are
+//! kept as is, without interpolation:
+//!
+//! ```xml
+//! <label>Fish &amp; Chips</label>
+//! <p>
+//!     Long copy, which can be written
+//!     on multiple lines.
+//! </p>
+//! <label><![CDATA[<b> is not a tag]]></label>
+//! ```
+//!
+//! ### Interpolation
+//!
+//! Any attribute can mix text with JSON state lookups between braces,
+//! each optionally followed by formatters:
+//!
+//! ```xml
+//! <label text="Hello {root:user.name}, you have {root:count} {root:count|plural(item,items)}" />
+//! <label text="Total: {root:cart.total|fixed(2)} ({root:cart.date|date})" />
+//! ```
+//!
+//! Formatters are `upper`, `lower`, `fixed(decimals)`, `date` (a Unix
+//! timestamp in seconds, displayed as `YYYY-MM-DD`) and
+//! `plural(singular,plural)`, which selects a word depending on the
+//! number. Braces are escaped by doubling them: `{{` & `}}`; braces which
+//! don't enclose a `namespace:path` lookup are kept as is. `pattern`
+//! attributes are never interpolated.
+//!
+//! ### Translations
+//!
+//! The `tr` namespace looks messages up in the translation tables of
+//! the app (see `Application::add_translations`), for its current locale:
+//!
+//! ```xml
+//! <label tr:text="inbox.title" />
+//! <label tr:text="inbox.unread(root:unread-count)" />
+//! <label text="{tr:inbox.title} ({root:unread-count})" />
+//! ```
+//!
+//! A translation table is a JSON object keyed by message ID. A message
+//! which depends on a number is an object keyed by plural category; the
+//! number is given between parentheses after the message ID. Messages
+//! can contain interpolated lookups:
+//!
+//! ```json
+//! {
+//!     "inbox": {
+//!         "title": "Inbox",
+//!         "unread": {
+//!             "one": "One unread message",
+//!             "other": "{root:unread-count} unread messages"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! `Application::set_locale` only re-creates the nodes which use
+//! translated messages.
+//!
+//! ### Input validation
+//!
+//! Insertions which cannot lead to a valid value are rejected. Other
+//! invalid values (incomplete ones, or after a deletion) are displayed
+//! with the `warn-inert` / `warn-focus` style of the theme. `pattern`
+//! replaces the built-in pattern of `integer` and `number` inputs.
+//!
+//! Valid `integer` and `number` values are stored as JSON numbers.
+//!
+//! ```xml
+//! <label root:text="age" editable="true" input="integer" />
+//! <label root:text="zip" editable="true" pattern="[0-9]{5}" />
+//! ```
 //!
 //! ## `<label>`
 //!
@@ -117,11 +522,48 @@
 //! of this label in its container. If it's absent, the label takes as
 //! much space as required for its content.
 //!
+//! ### Special Attribute: `overflow`
+//!
+//! What to do when the text doesn't fit in the label:
+//! - `shrink` (default): the font size is reduced so that the text fits
+//! - `ellipsis`: the text is truncated and ends with an ellipsis (…)
+//! - `clip`: the text is cut at the edge of the label
+//!
+//! With `ellipsis` and `clip`, labels in horizontal containers take all
+//! remaining space (unless `weight` is specified). In vertical containers,
+//! the font size is reduced until it reaches `min-size`; then the text
+//! overflows.
+//!
+//! ### Special Attributes: `min-size`, `max-size`
+//!
+//! Optional bounds of the font size, in pixels. The label is laid out
+//! accordingly: in vertical containers, its height is clamped; with
+//! `shrink` in horizontal containers, its width is the one of the
+//! clamped text, which is cut if it is higher than the container.
+//!
+//! ### Special Attribute: `mask`
+//!
+//! If present, every character of the text is displayed as the first
+//! character of this attribute, as in password fields. The JSON state
+//! keeps the real text, but copying it is disabled.
+//!
+//! ```xml
+//! <label root:text="password" editable="true" mask="•" />
+//! ```
+//!
 //! ## `<p>`
 //!
 //! Text displayed as a paragraph, with automatic carriage returns.
 //!
-//! Special Attribute: `size` (font-size, defaults to 24 pixels)
+//! Lines are broken at [UAX #14](https://www.unicode.org/reports/tr14/)
+//! line break opportunities: after whitespace, after hyphens, between
+//! CJK ideographs, etc. Line feeds always start a new line.
+//!
+//! Special Attributes:
+//! - `size` (font-size, defaults to 24 pixels)
+//! - `soft-hyphens`: whether or not soft hyphens (U+00AD) are line break
+//!   opportunities; when a line is broken there, a visible hyphen is drawn.
+//!   Defaults to `true`.
 //!
 //! # PNG Images
 //!
@@ -132,6 +574,13 @@
 //! Special Attribute: `file` (name of the asset, no default)
 
 pub mod container;
+pub mod button;
+pub mod checkbox;
+pub mod slider;
+pub mod tooltip;
+pub mod popup;
+pub mod conditional;
+pub mod template;
 pub mod inflate;
 pub mod png;
 pub mod railway;
@@ -1,19 +1,33 @@
 use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
 use crate::core::event::{Handlers, DEFAULT_HANDLERS, UserInputEvent};
-use crate::core::visual::{aspect_ratio, LayoutMode, Axis, Pixels};
-use crate::core::glyph::{space_width, get_font, load_font_bytes};
+use crate::core::visual::{LayoutMode, Axis, Pixels};
+use crate::core::glyph::{get_font, load_font_bytes};
 use crate::core::app::{Application, UNBREAKABLE_MUTATOR_INDEX};
 use crate::core::node::{NodeKey, Mutator, MutatorIndex};
-use crate::core::text_edit::{text_edit, break_ws};
+use crate::core::text_edit::{text_edit, initializer as text_edit_initializer, display_text, is_editable, break_lines, break_lines_with_mandatory, input_style, TextAttributes, LineBreaks, SOFT_HYPHEN};
 use crate::{
-    DEFAULT_FONT_NAME, DEFAULT_FONT_SIZE, FALSE_STR,
-    Error, error, ArcStr, ro_string, Box,
+    DEFAULT_FONT_NAME, DEFAULT_FONT_SIZE, FALSE_STR, TRUE_STR, TEXT_STR,
+    Error, error, ArcStr, ro_string, Box, Vec, format,
 };
 
 const TEXT: usize = 0;
 const FONT: usize = 1;
 const SIZE: usize = 2;
 const EDITABLE: usize = 3;
+const SOFT_HYPHENS: usize = 4;
+const PLACEHOLDER: usize = 5;
+const INPUT: usize = 6;
+const PATTERN: usize = 7;
+
+const TEXT_ATTRIBUTES: TextAttributes = TextAttributes {
+    editable: EDITABLE,
+    font: FONT,
+    text: TEXT,
+    placeholder: PLACEHOLDER,
+    mask: None,
+    input: INPUT,
+    pattern: PATTERN,
+};
 
 pub const PARAGRAPH_MUTATOR: Mutator = Mutator {
     name: ro_string!("ParagraphMutator"),
@@ -23,11 +37,17 @@ pub const PARAGRAPH_MUTATOR: Mutator = Mutator {
             ("text", AttributeValueType::Other, None),
             ("font", AttributeValueType::Other, Some(DEFAULT_FONT_NAME)),
             ("size", AttributeValueType::Pixels, Some(DEFAULT_FONT_SIZE)),
-            ("editable", AttributeValueType::Other, Some(FALSE_STR)),
+            ("editable", AttributeValueType::Boolean, Some(FALSE_STR)),
+            ("soft-hyphens", AttributeValueType::Boolean, Some(TRUE_STR)),
+            ("placeholder", AttributeValueType::OptOther, None),
+            ("input", AttributeValueType::Other, Some(TEXT_STR)),
+            ("pattern", AttributeValueType::OptOther, None),
         ],
         accepts_children: false,
+        text_content: Some(TEXT),
     }),
     handlers: Handlers {
+        initializer: text_edit_initializer,
         populator,
         parser,
         finalizer,
@@ -42,23 +62,24 @@ pub const UNBREAKABLE_MUTATOR: Mutator = Mutator {
     name: ro_string!("UnbreakableMutator"),
     xml_params: None,
     handlers: Handlers {
+        resizer: unbreakable_resizer,
         ..DEFAULT_HANDLERS
     },
     storage: None,
 };
 
 fn populator(app: &mut Application, _m: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
-    let text:      ArcStr = app.attr(node_key, TEXT)?;
+    let (text, _) = display_text(app, node_key, TEXT_ATTRIBUTES)?;
     let font_file: ArcStr = app.attr(node_key, FONT)?;
 
     let parent = app.view.parent(node_key).ok_or_else(|| error!())?;
     if app.view[parent].config.get_content_axis() != Axis::Vertical {
-        let xml_node = &app.xml_tree[xml_node_key];
-        let line = xml_node.line.get().unwrap_or(0.into());
-        return Err(error!("Paragraph is in an horizontal container; this is invalid! (line {})", line));
+        let location = app.xml_location(xml_node_key);
+        return Err(error!("{}: Paragraph is in an horizontal container; this is invalid!", location));
     }
 
-    match text.len() > 0 {
+    // empty editable paragraphs still need a caret and a clickable area
+    match !text.is_empty() || is_editable(app, node_key, TEXT_ATTRIBUTES)? {
         true => app.request(&font_file, node_key, true),
         false => Ok(()),
     }
@@ -68,22 +89,32 @@ fn parser(app: &mut Application, _m: MutatorIndex, _node_key: NodeKey, asset: &A
     load_font_bytes(app, asset, bytes)
 }
 
+fn line_breaks(app: &mut Application, node_key: NodeKey) -> Result<LineBreaks, Error> {
+    Ok(LineBreaks::Uax14 {
+        soft_hyphens: app.attr(node_key, SOFT_HYPHENS)?,
+    })
+}
+
 fn finalizer(app: &mut Application, _m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
-    let text:      ArcStr = app.attr(node_key, TEXT)?;
+    let (text, _) = display_text(app, node_key, TEXT_ATTRIBUTES)?;
     let font_file: ArcStr = app.attr(node_key, FONT)?;
     let font_size: Pixels = app.attr(node_key, SIZE)?;
+    let line_breaks = line_breaks(app, node_key)?;
     let font_size = font_size.to_num();
 
-    if text.len() > 0 {
+    if !text.is_empty() || is_editable(app, node_key, TEXT_ATTRIBUTES)? {
 
         let font = get_font(&mut app.mutators, &font_file).unwrap();
 
-        for unbreakable in break_ws(&text) {
+        // an empty unbreakable holds the caret of empty paragraphs
+        let caret = text.is_empty().then_some("");
+
+        for unbreakable in break_lines(&text, line_breaks).chain(caret) {
             let new_node = app.view.create();
 
-            let width = font.quick_width(&unbreakable, font_size);
-            let ratio = aspect_ratio(width, font_size);
-            app.view[new_node].config.set_layout_mode(LayoutMode::AspectRatio(ratio));
+            // the resizer adjusts this once lines are known
+            let width = Pixels::from_num(font.quick_width(unbreakable, font_size));
+            app.view[new_node].config.set_layout_mode(LayoutMode::Fixed(width));
 
             let factory = Some(UNBREAKABLE_MUTATOR_INDEX.into()).into();
             app.view[new_node].factory = factory;
@@ -91,51 +122,158 @@ fn finalizer(app: &mut Application, _m: MutatorIndex, node_key: NodeKey) -> Resu
             app.view.append_children(new_node, node_key);
         }
 
+        // whitespace is part of unbreakables
         let row = Pixels::from_num(font_size);
-        let gap = Pixels::from_num(space_width(font_size));
         app.view[node_key].config.set_layout_mode(LayoutMode::Chunks(row));
         app.view[node_key].config.set_content_axis(Axis::Horizontal);
-        app.view[node_key].config.set_content_gap(gap);
+        app.view[node_key].config.set_content_gap(Pixels::ZERO);
         app.invalidate_layout();
     }
 
     Ok(())
 }
 
+/// An unbreakable, as seen by [`break_paragraph`]
+#[derive(Debug, Copy, Clone)]
+struct Unbreakable {
+    width: Pixels,
+    /// Width with a visible hyphen, if it ends with a soft hyphen
+    hyphenated: Option<Pixels>,
+    /// Whether it ends with a mandatory break
+    mandatory: bool,
+}
+
+/// Breaks a paragraph into lines of `max_width` pixels
+///
+/// Returns the width of each unbreakable in the layout and whether it
+/// ends a line. Unbreakables ending with a soft hyphen only fit on a line
+/// if their hyphen fits too, but they only have room for it at the end
+/// of a line. The last unbreakable of a line fills the rest of the line,
+/// so that the chunks layout of the paragraph breaks lines at the same
+/// places; this also forces new lines after mandatory breaks.
+fn break_paragraph(unbreakables: &[Unbreakable], max_width: Pixels) -> Vec<(Pixels, bool)> {
+    let mut lines: Vec<(Pixels, bool)> = Vec::with_capacity(unbreakables.len());
+    let mut line_start = 0;
+    let mut used = Pixels::ZERO;
+
+    let end_line = |lines: &mut Vec<(Pixels, bool)>, line_start: usize| {
+        let last = lines.len() - 1;
+        let before: Pixels = lines[line_start..last].iter().map(|(width, _)| *width).sum();
+        let needed = unbreakables[last].hyphenated.unwrap_or(unbreakables[last].width);
+        lines[last] = (max_width.saturating_sub(before).max(needed), true);
+    };
+
+    for (i, unbreakable) in unbreakables.iter().enumerate() {
+        let needed = unbreakable.hyphenated.unwrap_or(unbreakable.width);
+
+        if i > line_start && used + needed > max_width {
+            end_line(&mut lines, line_start);
+            line_start = i;
+            used = Pixels::ZERO;
+        }
+
+        lines.push((unbreakable.width, false));
+        used += unbreakable.width;
+
+        if unbreakable.mandatory && i + 1 < unbreakables.len() {
+            end_line(&mut lines, line_start);
+            line_start = i + 1;
+            used = Pixels::ZERO;
+        }
+    }
+
+    lines
+}
+
 fn resizer(app: &mut Application, _m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
     let font_file:       ArcStr = app.attr(node_key, FONT)?;
     let font_size:       Pixels = app.attr(node_key, SIZE)?;
-    let text:            ArcStr = app.attr(node_key, TEXT)?;
+    let (text, is_placeholder) = display_text(app, node_key, TEXT_ATTRIBUTES)?;
+    let line_breaks = line_breaks(app, node_key)?;
+    let editable = is_editable(app, node_key, TEXT_ATTRIBUTES)?;
 
     let font_size = font_size.to_num();
     let is_focused = Some(node_key) == app.get_explicit_focus();
-    let inherited_style = app.get_inherited_style(node_key)?;
+    let style = input_style(app, node_key, TEXT_ATTRIBUTES)?;
 
-    if text.len() > 0 && !app.debug.skip_glyph_rendering {
-        let font = match get_font(&mut app.mutators, &font_file) {
-            Some(font) => font,
-            None => return Ok(()),
-        };
+    if text.is_empty() && !editable {
+        return Ok(());
+    }
 
-        let mut child = app.view.first_child(node_key).unwrap();
-        let mut unbrk_index = 0;
-        for unbreakable in break_ws(&text) {
-            let cursors = match is_focused {
-                true => Some((unbrk_index, app.text_cursors.as_slice())),
-                false => None,
-            };
-
-            let color = Some(inherited_style.foreground);
-            app.view[child].config.set_dirty(true);
-            app.view[child].foreground = {
-                let mut renderer = font.renderer(color, cursors, font_size);
-                renderer.write(&unbreakable);
-                renderer.texture()
-            };
-
-            child = app.view.next_sibling(child);
-            unbrk_index += 1;
+    let font = match get_font(&mut app.mutators, &font_file) {
+        Some(font) => font,
+        None => return Ok(()),
+    };
+
+    let caret = text.is_empty().then_some(("", true));
+    let measured: Vec<_> = break_lines_with_mandatory(&text, line_breaks).chain(caret).map(|(unbreakable, mandatory)| Unbreakable {
+        width: Pixels::from_num(font.quick_width(unbreakable, font_size)),
+        hyphenated: unbreakable.ends_with(SOFT_HYPHEN)
+            .then(|| Pixels::from_num(font.quick_width(&format!("{}-", unbreakable), font_size))),
+        mandatory,
+    }).collect();
+
+    let node = &app.view[node_key];
+    let max_width = node.size.w.saturating_sub(node.margin.total_on(Axis::Horizontal)).floor();
+    let lines = break_paragraph(&measured, max_width);
+
+    // unbreakables must be in their lines before being rendered
+    let mut relayout = false;
+    let mut child = app.view.first_child(node_key).unwrap();
+    for (width, _) in &lines {
+        if app.view[child].config.get_layout_mode() != LayoutMode::Fixed(*width) {
+            app.view[child].config.set_layout_mode(LayoutMode::Fixed(*width));
+            relayout = true;
         }
+
+        child = app.view.next_sibling(child);
+    }
+
+    if relayout {
+        app.invalidate_layout();
+        return Ok(());
+    }
+
+    if app.debug.skip_glyph_rendering {
+        return Ok(());
+    }
+
+    let strings = break_lines(&text, line_breaks).chain(caret.map(|(caret, _)| caret));
+    let mut child = app.view.first_child(node_key).unwrap();
+    for (unbrk_index, (unbreakable, (_, line_end))) in strings.zip(&lines).enumerate() {
+        let cursors = match is_focused {
+            true => Some((unbrk_index, app.text_cursors.as_slice())),
+            false => None,
+        };
+
+        let color = Some(match is_placeholder {
+            true => style.dimmed_foreground(),
+            false => style.foreground,
+        });
+
+        app.view[child].config.set_dirty(true);
+        app.view[child].foreground = {
+            let mut renderer = font.renderer(color, cursors, font_size);
+            renderer.write(unbreakable);
+
+            // this is the last unbreakable of its line
+            if *line_end && unbreakable.ends_with(SOFT_HYPHEN) {
+                renderer.write("-");
+            }
+
+            renderer.texture()
+        };
+
+        child = app.view.next_sibling(child);
+    }
+
+    Ok(())
+}
+
+/// When the layout resizes an unbreakable, its paragraph must re-render it
+fn unbreakable_resizer(app: &mut Application, _m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    if let Some(paragraph) = app.view.parent(node_key) {
+        app.view[paragraph].config.set_resized(true);
     }
 
     Ok(())
@@ -149,5 +287,51 @@ fn user_input_handler(
     event: &UserInputEvent,
 ) -> Result<bool, Error> {
     let font_size: Pixels = app.attr(node_key, SIZE)?;
-    text_edit(true, app, node_key, event, EDITABLE, FONT, font_size, TEXT)
+    let line_breaks = line_breaks(app, node_key)?;
+    text_edit(line_breaks, app, node_key, event, TEXT_ATTRIBUTES, font_size)
+}
+
+#[test]
+fn paragraph_lines() {
+    let px = Pixels::from_num;
+    let unbreakable = |width, hyphenated: Option<i32>, mandatory| Unbreakable {
+        width: px(width),
+        hyphenated: hyphenated.map(px),
+        mandatory,
+    };
+
+    // "co\u{AD}" only has room for its hyphen at the end of a line
+    let text = [
+        unbreakable(30, None, false),
+        unbreakable(20, Some(25), false),
+        unbreakable(40, None, false),
+        unbreakable(20, Some(25), false),
+        unbreakable(30, None, true),
+    ];
+
+    let lines = break_paragraph(&text, px(100));
+    assert_eq!(lines, [
+        (px(30), false),
+        (px(20), false),
+        (px(50), true),
+        (px(20), false),
+        (px(30), false),
+    ]);
+
+    // at the end of a line, it has room for its hyphen
+    let text = [
+        unbreakable(60, None, false),
+        unbreakable(20, Some(25), false),
+        unbreakable(30, None, true),
+    ];
+
+    assert_eq!(break_paragraph(&text, px(100)), [(px(60), false), (px(40), true), (px(30), false)]);
+
+    // a line feed starts a new line
+    let text = [
+        unbreakable(30, None, true),
+        unbreakable(30, None, true),
+    ];
+
+    assert_eq!(break_paragraph(&text, px(100)), [(px(100), true), (px(30), false)]);
 }
@@ -17,6 +17,7 @@ pub const PNG_MUTATOR: Mutator = Mutator {
         tag_name: ro_string!("png"),
         attr_set: &[ ("file", AttributeValueType::Other, None) ],
         accepts_children: false,
+        text_content: None,
     }),
     handlers: Handlers {
         initializer,
@@ -0,0 +1,120 @@
+use crate::core::app::Application;
+use crate::core::event::{Handlers, DEFAULT_HANDLERS};
+use crate::core::node::{NodeKey, Mutator, MutatorIndex};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
+use crate::core::visual::{Pixels, Margin, Axis, LayoutMode, PixelSource, MAX_CONTENT_GAP};
+use crate::core::overlay::Anchor;
+use crate::core::for_each_child;
+use crate::{FALSE_STR, Error, error, ArcStr, ro_string};
+use core::{mem::ManuallyDrop, time::Duration};
+use oakwood::NodeKey as _;
+
+const OPEN:   usize = 0;
+const MODAL:  usize = 1;
+const ANCHOR: usize = 2;
+const STYLE:  usize = 3;
+const GAP:    usize = 4;
+
+const BELOW: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("below"));
+const MENU_1: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("menu-1"));
+const DEFAULT_GAP: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("6"));
+
+const PADDING: Pixels = Pixels::lit("6");
+
+pub const POPUP_MUTATOR: Mutator = Mutator {
+    name: ro_string!("PopupMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("popup"),
+        attr_set: &[
+            ("open", AttributeValueType::Boolean, None),
+            ("modal", AttributeValueType::Boolean, Some(FALSE_STR)),
+            ("anchor", AttributeValueType::Other, Some(BELOW)),
+            ("style", AttributeValueType::Other, Some(MENU_1)),
+            ("gap", AttributeValueType::Pixels, Some(DEFAULT_GAP)),
+        ],
+        accepts_children: true,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        populator,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+/// Holder of the overlay opened by a `<popup>` node, if any
+fn find_overlay(app: &Application, node_key: NodeKey) -> Option<NodeKey> {
+    let xml_node_index = app.view[node_key].xml_node_index.get()?;
+    let owner = app.view.parent(node_key)?;
+    let mut overlays = app.overlays().iter();
+    overlays.find(|o| o.owner == owner && o.xml_node_index == xml_node_index).map(|o| o.holder)
+}
+
+/// In the main view, `<popup>` nodes take no space: they open (or close)
+/// an overlay, in which they are populated as a vertical container.
+fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    let parent = app.view.parent(node_key);
+    if !app.overlays().iter().any(|o| Some(o.holder) == parent) {
+        app.view[node_key].config.set_layout_mode(LayoutMode::Unset);
+
+        // the view might be in the middle of a reload
+        app.set_timer(node_key, Duration::ZERO, sync_overlay);
+        return Ok(());
+    }
+
+    let gap: Pixels = app.attr(node_key, GAP)?;
+    if gap > MAX_CONTENT_GAP {
+        return Err(error!("{}: Gap is too large: {} (max: {})", app.xml_location(xml_node_key), gap, MAX_CONTENT_GAP));
+    }
+
+    let style_name: ArcStr = app.attr(node_key, STYLE)?;
+    let style_index = app.theme.resolve(&style_name)
+        .ok_or_else(|| error!("{}: Invalid style name: {}", app.xml_location(xml_node_key), style_name))?;
+    let style = app.theme.get(style_index);
+
+    app.view[node_key].style_override = Some(style_index.into()).into();
+    app.view[node_key].background = PixelSource::SolidColor(style.background);
+    app.view[node_key].margin = Margin::quad(PADDING);
+    app.view[node_key].config.set_content_axis(Axis::Vertical);
+    app.view[node_key].config.set_content_gap(gap);
+    app.view[node_key].config.set_layout_mode(LayoutMode::WrapContent);
+    app.invalidate_layout();
+
+    for_each_child!(app.xml_tree, xml_node_key, xml_child, {
+        let child_node = app.view.create();
+        app.view.append_children(child_node, node_key);
+        app.view[child_node].xml_node_index = Some(xml_child.index()).into();
+        app.view[child_node].factory = app.xml_tree[xml_child].factory;
+
+        app.call_populator(child_node, xml_child)?;
+    });
+
+    Ok(())
+}
+
+/// Opens or closes the overlay of a `<popup>` node, depending on its `open` attribute
+fn sync_overlay(app: &mut Application, node_key: NodeKey) -> Result<(), Error> {
+    let open: bool = app.attr(node_key, OPEN)?;
+
+    match (open, find_overlay(app, node_key)) {
+        (true, None) => {
+            let modal: bool = app.attr(node_key, MODAL)?;
+            let anchor: ArcStr = app.attr(node_key, ANCHOR)?;
+            let anchor = match &*anchor {
+                "below" => Anchor::Below,
+                "pointer" => Anchor::At(app.get_focus_coords()),
+                "center" => Anchor::Center,
+                _ => return Err(error!("{}: Invalid anchor: {} (expected below, pointer or center)", app.node_location(node_key), anchor)),
+            };
+
+            let owner = app.view.parent(node_key).unwrap();
+            let xml_node_index = app.view[node_key].xml_node_index.get().unwrap();
+            let xml_node_key = app.xml_tree.node_key(xml_node_index);
+            app.push_overlay(owner, xml_node_key, anchor, modal)?;
+        },
+        (false, Some(holder)) => app.remove_overlay(holder)?,
+        _ => (),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use crate::core::app::Application;
+use crate::core::event::{Handlers, DEFAULT_HANDLERS, UserInputEvent};
+use crate::core::node::{NodeKey, Mutator, MutatorIndex, get_storage};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
+use crate::core::visual::{Ratio, Pixels, Axis, Direction, LayoutMode, PixelSource, RgbaPixelArray};
+use crate::core::rgb::{FromSlice, RGBA8};
+use crate::{SSAA, SSAA_SQ, ZERO_ARCSTR, ONE_ARCSTR, DEFAULT_FONT_SIZE, Error, error, Box, Vec, vec, ro_string};
+use vek::num_traits::Float;
+
+use railway::{NaiveRenderer, computing::Couple};
+
+type R = NaiveRenderer<&'static [u8]>;
+
+const VALUE: usize = 0;
+const MIN:   usize = 1;
+const MAX:   usize = 2;
+const STEP:  usize = 3;
+const SIZE:  usize = 4;
+
+/// Number of wheel / arrow key increments between
+/// `min` and `max` when `step` is unspecified
+const DEFAULT_INCREMENTS: f64 = 20.0;
+
+pub const SLIDER_MUTATOR: Mutator = Mutator {
+    name: ro_string!("SliderMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("slider"),
+        attr_set: &[
+            ("value", AttributeValueType::Number, None),
+            ("min", AttributeValueType::Number, Some(ZERO_ARCSTR)),
+            ("max", AttributeValueType::Number, Some(ONE_ARCSTR)),
+            ("step", AttributeValueType::OptNumber, None),
+            ("size", AttributeValueType::Pixels, Some(DEFAULT_FONT_SIZE)),
+        ],
+        accepts_children: false,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        initializer,
+        populator,
+        resizer,
+        user_input_handler,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+/// Railway program drawing the track, its filled part & the thumb
+struct SliderStorage {
+    railway: R,
+    mask: Vec<u8>,
+}
+
+fn initializer(app: &mut Application, m: MutatorIndex) -> Result<(), Error> {
+    let storage = &mut app.mutators[usize::from(m)].storage;
+    assert!(storage.is_none());
+
+    let railway = R::parse(include_bytes!(concat!(env!("OUT_DIR"), "/slider.rwy"))).unwrap();
+    *storage = Some(Box::new(SliderStorage {
+        railway,
+        mask: Vec::new(),
+    }));
+
+    Ok(())
+}
+
+struct Range {
+    min: f64,
+    max: f64,
+    step: Option<f64>,
+}
+
+impl Range {
+    fn get(app: &mut Application, node_key: NodeKey) -> Result<Self, Error> {
+        Ok(Self {
+            min: app.attr(node_key, MIN)?,
+            max: app.attr(node_key, MAX)?,
+            step: app.attr(node_key, STEP)?,
+        })
+    }
+
+    /// Position of `value` in the range, from 0 to 1
+    fn fraction(&self, value: f64) -> f64 {
+        match self.max > self.min {
+            true => ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0),
+            false => 0.0,
+        }
+    }
+
+    /// Clamps a value to the range and snaps it to the closest step
+    fn constrain(&self, value: f64) -> f64 {
+        let value = match self.step {
+            Some(step) if step > 0.0 => self.min + Float::round((value - self.min) / step) * step,
+            _ => value,
+        };
+
+        value.min(self.max).max(self.min)
+    }
+
+    /// Value added or substracted by wheel & arrow key increments
+    fn increment(&self) -> f64 {
+        match self.step {
+            Some(step) if step > 0.0 => step,
+            _ => (self.max - self.min) / DEFAULT_INCREMENTS,
+        }
+    }
+}
+
+fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, _: XmlNodeKey) -> Result<(), Error> {
+    let size: Pixels = app.attr(node_key, SIZE)?;
+
+    let parent = app.view.parent(node_key).ok_or_else(|| error!())?;
+    let layout_mode = match app.view[parent].config.get_content_axis() {
+        Axis::Horizontal => LayoutMode::Remaining(Ratio::ONE),
+        Axis::Vertical => LayoutMode::Fixed(size),
+    };
+
+    app.view[node_key].config.set_layout_mode(layout_mode);
+    app.invalidate_layout();
+
+    Ok(())
+}
+
+fn rg_ba(color: RGBA8) -> (Couple, Couple) {
+    let rg = Couple::new((color.r as f32) / 255.0, (color.g as f32) / 255.0);
+    let ba = Couple::new((color.b as f32) / 255.0, (color.a as f32) / 255.0);
+    (rg, ba)
+}
+
+fn resolve_style(app: &Application, name: &str) -> Result<usize, Error> {
+    app.theme.resolve(name).ok_or_else(|| error!("Missing {} style in theme", name))
+}
+
+fn resizer(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    let value: f64 = app.attr(node_key, VALUE)?;
+    let fraction = Range::get(app, node_key)?.fraction(value) as f32;
+
+    let track = app.theme.get(resolve_style(app, "neutral-inert")?).background;
+    let fill = app.theme.get(resolve_style(app, "incite-focus")?).background;
+    let thumb = app.theme.get(resolve_style(app, "neutral-focus")?).foreground;
+
+    let size = app.view[node_key].size;
+    let (w, h): (usize, usize) = (size.w.to_num(), size.h.to_num());
+    let (wf, hf) = (w as f32, h as f32);
+
+    let radius = hf / 2.0;
+    let thumb_x = radius + fraction * (wf - hf).max(0.0);
+
+    let (track_rg, track_ba) = rg_ba(track);
+    let (fill_rg, fill_ba) = rg_ba(fill);
+    let (thumb_rg, thumb_ba) = rg_ba(thumb);
+
+    let storage: &mut SliderStorage = get_storage(&mut app.mutators, m).unwrap();
+    let railway = &mut storage.railway;
+    railway.set_argument("size", Couple::new(wf, hf)).unwrap();
+    railway.set_argument("track-start", Couple::new(radius, radius)).unwrap();
+    railway.set_argument("track-end", Couple::new((wf - radius).max(radius), radius)).unwrap();
+    railway.set_argument("thumb", Couple::new(thumb_x, radius)).unwrap();
+    railway.set_argument("radii", Couple::new(hf / 6.0, (radius - 1.0).max(0.0))).unwrap();
+    railway.set_argument("track-rg", track_rg).unwrap();
+    railway.set_argument("track-ba", track_ba).unwrap();
+    railway.set_argument("fill-rg", fill_rg).unwrap();
+    railway.set_argument("fill-ba", fill_ba).unwrap();
+    railway.set_argument("thumb-rg", thumb_rg).unwrap();
+    railway.set_argument("thumb-ba", thumb_ba).unwrap();
+    railway.compute().unwrap();
+
+    let mut canvas = vec![0u8; w * h * 4];
+    storage.mask.resize(w * h, 0);
+    railway.render::<SSAA, SSAA_SQ>(canvas.as_rgba_mut(), &mut storage.mask, w, h, w, true).unwrap();
+
+    app.view[node_key].config.set_dirty(true);
+    app.view[node_key].foreground = {
+        let canvas = canvas.into_boxed_slice();
+        PixelSource::TextureNoSSAA(Box::new(RgbaPixelArray::new(canvas, w, h)))
+    };
+
+    Ok(())
+}
+
+/// Writes a new value in the JSON state, after clamping & snapping it
+fn set_value(app: &mut Application, node_key: NodeKey, value: f64) -> Result<(), Error> {
+    let value_path = match app.attr_state_path(node_key, VALUE)? {
+        Ok((path, _)) => path,
+        Err(_) => {
+            log::error!("Cannot update <slider>: \"value\" isn't a state path");
+            return Ok(());
+        },
+    };
+
+    let value = Range::get(app, node_key)?.constrain(value);
+    let old_value: f64 = app.attr(node_key, VALUE)?;
+
+    if value != old_value {
+        app.state.set_number(&value_path, value);
+        app.reload_view();
+    }
+
+    Ok(())
+}
+
+/// Sets the value from the horizontal position of the pointer
+fn set_value_from_pointer(app: &mut Application, node_key: NodeKey) -> Result<(), Error> {
+    let range = Range::get(app, node_key)?;
+    let position = app.view[node_key].position;
+    let size = app.view[node_key].size;
+
+    let radius = size.h.to_num::<f64>() / 2.0;
+    let track = size.w.to_num::<f64>() - 2.0 * radius;
+    let x = (app.get_focus_coords().x - position.x).to_num::<f64>() - radius;
+
+    // not laid out yet (e.g. right after a reload)
+    if track <= 0.0 {
+        return Ok(());
+    }
+
+    let fraction = (x / track).clamp(0.0, 1.0);
+    set_value(app, node_key, range.min + fraction * (range.max - range.min))
+}
+
+fn user_input_handler(
+    app: &mut Application,
+    _m: MutatorIndex,
+    node_key: NodeKey,
+    _target: NodeKey,
+    event: &UserInputEvent,
+) -> Result<bool, Error> {
+    let offset = match event {
+        UserInputEvent::QuickAction1 | UserInputEvent::Drag => {
+            if app.get_explicit_focus() != Some(node_key) {
+                app.set_focused_node(node_key)?;
+            }
+
+            set_value_from_pointer(app, node_key)?;
+            return Ok(true);
+        },
+        UserInputEvent::FocusLoss => return Ok(true),
+        UserInputEvent::WheelY(delta) => match delta.is_negative() {
+            true => -1.0,
+            false => 1.0,
+        },
+        UserInputEvent::DirInput(Direction::Up | Direction::Right) => 1.0,
+        UserInputEvent::DirInput(Direction::Down | Direction::Left) => -1.0,
+        _ => return Ok(false),
+    };
+
+    let value: f64 = app.attr(node_key, VALUE)?;
+    let increment = Range::get(app, node_key)?.increment();
+    set_value(app, node_key, value + offset * increment)?;
+
+    Ok(true)
+}
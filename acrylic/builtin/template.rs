@@ -0,0 +1,63 @@
+use crate::core::app::Application;
+use crate::core::event::{Handlers, DEFAULT_HANDLERS};
+use crate::core::node::{NodeKey, Mutator, MutatorIndex};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValueType};
+use crate::core::visual::LayoutMode;
+use crate::{Error, error, ro_string};
+
+pub(crate) const NAME:   usize = 0;
+pub(crate) const PARAMS: usize = 1;
+
+/// Templates are instantiated by the XML parser; the
+/// `<template>` node itself is left out of the layout.
+pub const TEMPLATE_MUTATOR: Mutator = Mutator {
+    name: ro_string!("TemplateMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("template"),
+        attr_set: &[
+            ("name", AttributeValueType::Other, None),
+            ("params", AttributeValueType::OptOther, None),
+        ],
+        accepts_children: true,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        populator: template_populator,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+/// Replaced by the children of template instances
+pub const SLOT_MUTATOR: Mutator = Mutator {
+    name: ro_string!("SlotMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("slot"),
+        attr_set: &[],
+        accepts_children: false,
+        text_content: None,
+    }),
+    handlers: Handlers {
+        populator: slot_populator,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+fn template_populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, _: XmlNodeKey) -> Result<(), Error> {
+    app.view[node_key].config.set_layout_mode(LayoutMode::Unset);
+    Ok(())
+}
+
+fn slot_populator(app: &mut Application, _: MutatorIndex, _: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    Err(error!("{}: <slot> can only be used in templates", app.xml_location(xml_node_key)))
+}
+
+#[test]
+fn slots_outside_templates() {
+    use crate::core::app::SimpleCallbackMap;
+
+    let mut app = Application::new("main.xml".into(), SimpleCallbackMap::new());
+    let error = app.data_response("main.xml".into(), b"<v-rem>\n    <slot />\n</v-rem>"[..].into()).unwrap_err();
+    assert_eq!(error.msg.as_deref(), Some("main.xml:2:5: <slot> can only be used in templates"));
+}
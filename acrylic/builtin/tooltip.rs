@@ -0,0 +1,302 @@
+use crate::core::app::{Application, TOOLTIP_MUTATOR_INDEX};
+use crate::core::event::{Handlers, DEFAULT_HANDLERS};
+use crate::core::node::{NodeKey, Mutator, MutatorIndex, get_storage};
+use crate::core::xml::{XmlNodeKey, XmlTagParameters, AttributeValue, AttributeValueType, AttributeValueVec};
+use crate::core::visual::{aspect_ratio, Pixels, SignedPixels, Position, Margin, Axis, LayoutMode, PixelSource};
+use crate::core::glyph::{get_font, load_font_bytes};
+use crate::core::overlay::Anchor;
+use crate::core::clock::TimerId;
+use crate::core::for_each_child;
+use crate::{DEFAULT_FONT_NAME, Error, error, ArcStr, Box, ro_string};
+use core::{mem::ManuallyDrop, time::Duration};
+use oakwood::NodeKey as _;
+
+const TEXT:  usize = 0;
+const FONT:  usize = 1;
+const SIZE:  usize = 2;
+const STYLE: usize = 3;
+
+const MENU_1: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("menu-1"));
+const TOOLTIP_FONT_SIZE: ManuallyDrop<ArcStr> = ManuallyDrop::new(ro_string!("16"));
+
+/// How long the pointer must rest on a node before its tooltip is shown
+const DELAY: Duration = Duration::from_millis(600);
+
+/// Offset of tooltips from the pointer
+const OFFSET: Position = Position::new(SignedPixels::lit("12"), SignedPixels::lit("20"));
+
+const PADDING: Pixels = Pixels::lit("6");
+
+pub const TOOLTIP_MUTATOR: Mutator = Mutator {
+    name: ro_string!("TooltipMutator"),
+    xml_params: Some(XmlTagParameters {
+        tag_name: ro_string!("tooltip"),
+        attr_set: &[
+            ("text", AttributeValueType::OptOther, None),
+            ("font", AttributeValueType::Other, Some(DEFAULT_FONT_NAME)),
+            ("size", AttributeValueType::Pixels, Some(TOOLTIP_FONT_SIZE)),
+            ("style", AttributeValueType::Other, Some(MENU_1)),
+        ],
+        accepts_children: true,
+        text_content: Some(TEXT),
+    }),
+    handlers: Handlers {
+        initializer,
+        populator,
+        parser,
+        finalizer,
+        resizer,
+        ..DEFAULT_HANDLERS
+    },
+    storage: None,
+};
+
+struct TooltipStorage {
+    /// Holder of the displayed tooltip; its owner is the hovered node
+    shown: Option<NodeKey>,
+    /// Pending timer, which will show a tooltip
+    timer: Option<TimerId>,
+    /// `<tooltip>` XML node created for the `tooltip` attribute of the
+    /// displayed tooltip's owner; it's deleted when the tooltip is hidden.
+    generated: Option<XmlNodeKey>,
+}
+
+/// Where the tooltip of a node comes from
+enum TooltipSource {
+    /// A `<tooltip>` XML child
+    Child(XmlNodeKey),
+    /// The value of a `tooltip` attribute
+    Attribute(AttributeValue),
+}
+
+fn initializer(app: &mut Application, m: MutatorIndex) -> Result<(), Error> {
+    let storage = &mut app.mutators[usize::from(m)].storage;
+    assert!(storage.is_none());
+
+    *storage = Some(Box::new(TooltipStorage {
+        shown: None,
+        timer: None,
+        generated: None,
+    }));
+
+    Ok(())
+}
+
+fn storage(app: &mut Application) -> &mut TooltipStorage {
+    get_storage(&mut app.mutators, TOOLTIP_MUTATOR_INDEX.into()).unwrap()
+}
+
+fn in_overlay(app: &Application, node_key: NodeKey) -> bool {
+    let parent = app.view.parent(node_key);
+    app.overlays().iter().any(|o| Some(o.holder) == parent)
+}
+
+/// `<tooltip>` nodes are only displayed in overlays;
+/// in the main view, they're left out of the layout.
+fn populator(app: &mut Application, _: MutatorIndex, node_key: NodeKey, xml_node_key: XmlNodeKey) -> Result<(), Error> {
+    if !in_overlay(app, node_key) {
+        app.view[node_key].config.set_layout_mode(LayoutMode::Unset);
+        return Ok(());
+    }
+
+    let font_size: Pixels = app.attr(node_key, SIZE)?;
+    let style_name: ArcStr = app.attr(node_key, STYLE)?;
+    let style_index = app.theme.resolve(&style_name)
+        .ok_or_else(|| error!("{}: Invalid style name: {}", app.xml_location(xml_node_key), style_name))?;
+    let style = app.theme.get(style_index);
+
+    app.view[node_key].style_override = Some(style_index.into()).into();
+    app.view[node_key].background = PixelSource::SolidColor(style.background);
+    app.view[node_key].margin = Margin::quad(PADDING);
+    app.view[node_key].config.set_content_axis(Axis::Horizontal);
+    app.view[node_key].config.set_layout_mode(LayoutMode::Fixed(font_size + PADDING + PADDING));
+    app.invalidate_layout();
+
+    // the text comes before XML children
+    let text: Option<ArcStr> = app.attr(node_key, TEXT)?;
+    if text.is_some() {
+        let font_file: ArcStr = app.attr(node_key, FONT)?;
+        app.request(&font_file, node_key, true)?;
+    }
+
+    for_each_child!(app.xml_tree, xml_node_key, xml_child, {
+        let child_node = app.view.create();
+        app.view.append_children(child_node, node_key);
+        app.view[child_node].xml_node_index = Some(xml_child.index()).into();
+        app.view[child_node].factory = app.xml_tree[xml_child].factory;
+
+        app.call_populator(child_node, xml_child)?;
+    });
+
+    Ok(())
+}
+
+fn parser(app: &mut Application, _m: MutatorIndex, _node_key: NodeKey, asset: &ArcStr, bytes: Box<[u8]>) -> Result<(), Error> {
+    load_font_bytes(app, asset, bytes)
+}
+
+/// Creates a text node for the `text` attribute
+fn finalizer(app: &mut Application, m: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    let text: Option<ArcStr> = app.attr(node_key, TEXT)?;
+    let text = match text {
+        Some(text) => text,
+        None => return Ok(()),
+    };
+
+    let font_file: ArcStr = app.attr(node_key, FONT)?;
+    let font_size: Pixels = app.attr(node_key, SIZE)?;
+    let font_size = font_size.to_num();
+
+    let font = get_font(&mut app.mutators, &font_file).unwrap();
+    let width = font.quick_width(&text, font_size);
+
+    let text_node = app.view.create();
+    app.view[text_node].factory = Some(m).into();
+    app.view[text_node].config.set_layout_mode(LayoutMode::AspectRatio(aspect_ratio(width, font_size)));
+    app.view.append_children(text_node, node_key);
+    app.invalidate_layout();
+
+    Ok(())
+}
+
+/// Renders the text node of a tooltip
+fn resizer(app: &mut Application, _: MutatorIndex, node_key: NodeKey) -> Result<(), Error> {
+    let tooltip = match app.view[node_key].xml_node_index.get() {
+        Some(_) => return Ok(()),
+        None => app.view.parent(node_key).unwrap(),
+    };
+
+    let text: Option<ArcStr> = app.attr(tooltip, TEXT)?;
+    let text = match text {
+        Some(text) => text,
+        None => return Ok(()),
+    };
+
+    let font_file: ArcStr = app.attr(tooltip, FONT)?;
+    let font_size: Pixels = app.attr(tooltip, SIZE)?;
+    let style = app.get_inherited_style(node_key)?;
+
+    if app.debug.skip_glyph_rendering {
+        return Ok(());
+    }
+
+    let font = match get_font(&mut app.mutators, &font_file) {
+        Some(font) => font,
+        None => return Ok(()),
+    };
+
+    app.view[node_key].config.set_dirty(true);
+    app.view[node_key].foreground = {
+        let mut renderer = font.renderer(Some(style.foreground), None, font_size.to_num());
+        renderer.write(&text);
+        renderer.texture()
+    };
+
+    Ok(())
+}
+
+/// Finds the tooltip of a node: either a `<tooltip>`
+/// XML child, or the value of its `tooltip` attribute.
+fn find_tooltip(app: &Application, node_key: NodeKey) -> Option<TooltipSource> {
+    let xml_node_index = app.view[node_key].xml_node_index.get()?;
+    let xml_node_key = app.xml_tree.node_key(xml_node_index);
+
+    for_each_child!(app.xml_tree, xml_node_key, xml_child, {
+        if app.xml_tree[xml_child].factory.get() == Some(TOOLTIP_MUTATOR_INDEX.into()) {
+            return Some(TooltipSource::Child(xml_child));
+        }
+    });
+
+    let mutator = &app.mutators[usize::from(app.view[node_key].factory.get()?)];
+    let attr_set = mutator.xml_params.as_ref()?.attr_set;
+    let index = attr_set.iter().position(|(name, _, _)| *name == "tooltip")?;
+    let text = app.xml_tree[xml_node_key].attributes.get(index).clone();
+
+    match text {
+        AttributeValue::Unset => None,
+        text => Some(TooltipSource::Attribute(text)),
+    }
+}
+
+/// Hides the tooltip when the pointer leaves its node, and
+/// starts a timer if the newly hovered node has a tooltip.
+pub(crate) fn hover_changed(app: &mut Application) -> Result<(), Error> {
+    if let Some(timer) = storage(app).timer.take() {
+        app.cancel_timer(timer);
+    }
+
+    if let Some(holder) = storage(app).shown {
+        // it might have been closed with an overlay below it
+        let overlay = app.overlays().iter().find(|o| o.holder == holder);
+
+        match overlay.map(|o| o.owner) {
+            Some(host) if app.is_hovered(host) => return Ok(()),
+            Some(_) => app.remove_overlay(holder)?,
+            None => (),
+        }
+
+        storage(app).shown = None;
+        if let Some(generated) = storage(app).generated.take() {
+            app.xml_tree.delete(generated);
+        }
+    }
+
+    let mut current = Some(app.get_implicit_focus());
+    while let Some(node_key) = current {
+        if find_tooltip(app, node_key).is_some() {
+            let timer = app.set_timer(node_key, DELAY, show_tooltip);
+            storage(app).timer = Some(timer);
+            break;
+        }
+
+        current = app.view.parent(node_key);
+    }
+
+    Ok(())
+}
+
+fn show_tooltip(app: &mut Application, node_key: NodeKey) -> Result<(), Error> {
+    storage(app).timer = None;
+
+    if let Some(source) = find_tooltip(app, node_key) {
+        let tooltip = match source {
+            TooltipSource::Child(xml_child) => xml_child,
+            TooltipSource::Attribute(text) => {
+                let mut attributes = AttributeValueVec::new(TOOLTIP_MUTATOR.xml_params.as_ref().unwrap());
+                attributes.set(TEXT, text);
+
+                let tooltip = app.xml_tree.create();
+                app.xml_tree[tooltip].factory = Some(TOOLTIP_MUTATOR_INDEX.into()).into();
+                app.xml_tree[tooltip].attributes = attributes;
+
+                storage(app).generated = Some(tooltip);
+                tooltip
+            },
+        };
+
+        let p = app.get_focus_coords();
+        let anchor = Anchor::At(Position::new(p.x + OFFSET.x, p.y + OFFSET.y));
+        let holder = app.push_overlay(node_key, tooltip, anchor, false)?;
+        storage(app).shown = Some(holder);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn shows_tooltips() {
+    use crate::core::app::{test_app, test_render};
+
+    let mut app = test_app("{}", r#"<h-rem><h-rem tooltip="Saves the file" /></h-rem>"#);
+    app.set_focus_coords(Position::new(SignedPixels::from_num(50), SignedPixels::from_num(50))).unwrap();
+    assert!(app.overlays().is_empty());
+
+    app.set_age(DELAY).unwrap();
+    test_render(&mut app);
+
+    let holder = app.overlays()[0].holder;
+    let tooltip = app.view.first_child(holder).unwrap();
+    let text_node = app.view.first_child(tooltip).unwrap();
+    assert_eq!(app.view[text_node].xml_node_index.get(), None);
+    assert!(app.view[text_node].size.w > Pixels::ZERO);
+}
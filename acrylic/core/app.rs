@@ -1,13 +1,17 @@
 //! The state of your Application
 
-use super::xml::{XmlNodeTree, XmlNodeKey, AttributeValue, AttributeValueVec, AttributeValueType};
-use crate::{Error, error, String, ArcStr, Vec, Box, Rc, HashMap, LiteMap, DEFAULT_FONT_NAME};
-use super::visual::{Pixels, Position, Size, write_framebuffer, constrain, Texture as _};
+use super::xml::{XmlNodeTree, XmlNodeKey, AttributeValue, AttributeValueVec, AttributeValueType, FileIndex, locate};
+use crate::{Error, error, String, ArcStr, Vec, vec, Box, Rc, HashMap, LiteMap, DEFAULT_FONT_NAME};
+use super::visual::{Pixels, SignedPixels, Position, Size, Overflow, PixelSource, write_framebuffer, constrain, Texture as _};
 use super::style::{Theme, Style, DEFAULT_STYLE};
-use super::layout::{compute_layout, hit_test};
-use super::node::{NodeTree, NodeKey, Mutator};
-use super::state::{Namespace, root_ns};
-use core::{time::Duration, ops::Deref};
+use super::layout::{compute_layout, compute_overlay_layout, get_scrollbar, hit_test, scroll_into_view};
+use super::overlay::{Overlay, Anchor, OVERLAY_MUTATOR};
+use super::clock::{Timer, TimerId, TimerCallback, Animation, AnimationCallback};
+use super::node::{NodeTree, NodeKey, Mutator, MutatorLibrary};
+use super::state::{Namespace, Item, Filter, root_ns, tr_ns};
+use super::locale::Localization;
+use super::interpolation::{Interpolation, Segment, display};
+use core::{time::Duration, ops::{Deref, Range}, str::from_utf8};
 use super::event::UserInputEvent;
 use super::text_edit::Cursor;
 use super::for_each_child;
@@ -22,6 +26,13 @@ use crate::builtin::{
     inflate::INFLATE_MUTATOR,
     import::IMPORT_MUTATOR,
     png::PNG_MUTATOR,
+    button::BUTTON_MUTATOR,
+    checkbox::{CHECKBOX_MUTATOR, TOGGLE_MUTATOR},
+    slider::SLIDER_MUTATOR,
+    tooltip::{TOOLTIP_MUTATOR, hover_changed},
+    popup::POPUP_MUTATOR,
+    conditional::{IF_MUTATOR, ELSE_MUTATOR, SWITCH_MUTATOR, CASE_MUTATOR, conditional_source, uses_namespace},
+    template::{TEMPLATE_MUTATOR, SLOT_MUTATOR},
     container::CONTAINERS,
     label::LABEL_MUTATOR,
     paragraph::{PARAGRAPH_MUTATOR, UNBREAKABLE_MUTATOR},
@@ -47,6 +58,18 @@ enum Asset {
     Raw(Rc<[u8]>),
 }
 
+/// A root (main view or overlay holder) and a path of child indices
+type NodePath = (NodeKey, Vec<usize>);
+
+/// Where stateful nodes were before a part of the view is re-created
+struct NodePaths {
+    explicit_focus: Option<NodePath>,
+    implicit_focus: NodePath,
+    timers: Vec<NodePath>,
+    animations: Vec<NodePath>,
+    scrolled: Vec<(NodePath, SignedPixels)>,
+}
+
 pub struct DebuggingOptions {
     pub skip_glyph_rendering: bool,
     pub skip_container_decoration: bool,
@@ -61,6 +84,7 @@ pub struct DebuggingOptions {
 /// - the XML layout
 /// - the JSON state and related triggers
 /// - the internal view representation (a Node tree)
+/// - the stack of [`Overlay`]s displayed above the view
 /// - the [`Theme`]
 /// - a cache of assets
 pub struct Application {
@@ -72,16 +96,26 @@ pub struct Application {
     pub debug: DebuggingOptions,
     pub state: JsonFile,
 
-    pub(crate) namespaces: LiteMap<NodeKey, Namespace>,
+    pub(crate) namespaces: LiteMap<NodeKey, Vec<Namespace>>,
     pub(crate) mutators: Vec<Mutator>,
     pub(crate) text_cursors: Vec<Cursor>,
+    pub(crate) clipboard: Option<ArcStr>,
+    pub(crate) localization: Localization,
+    overlays: Vec<Overlay>,
+    /// Areas which must be repainted, e.g. after an overlay was removed
+    damage: Vec<(Position, Size)>,
     implicit_focus: NodeKey,
 
     focus_coords: Position,
     explicit_focus: Option<NodeKey>,
     must_check_layout: bool,
-    _source_files: Vec<String>,
-    _age: Duration,
+    source_files: Vec<ArcStr>,
+    /// Prefixes & mutator indices of mutator libraries
+    pub(crate) libraries: Vec<(ArcStr, Range<usize>)>,
+    age: Duration,
+    timers: Vec<Timer>,
+    next_timer_id: usize,
+    animations: Vec<Animation>,
     render_list: Vec<(Position, Size)>,
     assets: HashMap<ArcStr, Asset>,
     requests: Vec<Request>,
@@ -90,6 +124,8 @@ pub struct Application {
 pub const IMPORT_MUTATOR_INDEX: usize = 0;
 pub const FONT_MUTATOR_INDEX: usize = 1;
 pub const UNBREAKABLE_MUTATOR_INDEX: usize = 5;
+pub const OVERLAY_MUTATOR_INDEX: usize = 11;
+pub const TOOLTIP_MUTATOR_INDEX: usize = 12;
 
 impl Application {
     /// Main constructor
@@ -102,11 +138,26 @@ impl Application {
             PARAGRAPH_MUTATOR,
             UNBREAKABLE_MUTATOR,
             INFLATE_MUTATOR,
+            BUTTON_MUTATOR,
+            CHECKBOX_MUTATOR,
+            TOGGLE_MUTATOR,
+            SLIDER_MUTATOR,
+            OVERLAY_MUTATOR,
+            TOOLTIP_MUTATOR,
+            IF_MUTATOR,
+            ELSE_MUTATOR,
+            SWITCH_MUTATOR,
+            CASE_MUTATOR,
+            TEMPLATE_MUTATOR,
+            SLOT_MUTATOR,
+            POPUP_MUTATOR,
         ];
 
         assert_eq!(default_mutators[IMPORT_MUTATOR_INDEX].name, "ImportMutator");
         assert_eq!(default_mutators[FONT_MUTATOR_INDEX].name, "FontMutator");
         assert_eq!(default_mutators[UNBREAKABLE_MUTATOR_INDEX].name, "UnbreakableMutator");
+        assert_eq!(default_mutators[OVERLAY_MUTATOR_INDEX].name, "OverlayMutator");
+        assert_eq!(default_mutators[TOOLTIP_MUTATOR_INDEX].name, "TooltipMutator");
 
         let mut mutators = Vec::with_capacity(default_mutators.len() + CONTAINERS.len());
         mutators.extend_from_slice(default_mutators);
@@ -122,13 +173,21 @@ impl Application {
             callbacks,
             mutators,
             must_check_layout: false,
-            _source_files: Vec::new(),
+            source_files: Vec::new(),
+            libraries: Vec::new(),
             text_cursors: Vec::new(),
+            clipboard: None,
+            localization: Localization::new(),
+            overlays: Vec::new(),
+            damage: Vec::new(),
             focus_coords: Position::zero(),
             implicit_focus: Default::default(),
             explicit_focus: None,
             theme: Theme::parse(include_str!("default-theme.json")).unwrap(),
-            _age: Duration::from_secs(0),
+            age: Duration::ZERO,
+            timers: Vec::new(),
+            next_timer_id: 0,
+            animations: Vec::new(),
             render_list: Vec::new(),
             debug: DebuggingOptions {
                 skip_glyph_rendering: false,
@@ -168,7 +227,7 @@ impl Application {
         app.view[app.root].factory = factory;
         app.view[app.root].xml_node_index = Some(xml_root.index().into()).into();
 
-        app.namespaces.insert(app.root, root_ns());
+        app.namespaces.insert(app.root, vec![root_ns(), tr_ns()]);
         app.implicit_focus = app.root;
 
         app.call_populator(app.root, xml_root).unwrap();
@@ -176,7 +235,9 @@ impl Application {
         app
     }
 
-    fn node_path(&self, mut node_key: NodeKey) -> Vec<usize> {
+    /// Returns the root of the layer (main view or overlay) containing
+    /// a node, and the path from this root to the node.
+    fn node_path(&self, mut node_key: NodeKey) -> NodePath {
         let mut path = Vec::new();
 
         while let Some(parent) = self.view.parent(node_key) {
@@ -185,36 +246,39 @@ impl Application {
             node_key = parent;
         }
 
-        path
+        (node_key, path)
     }
 
-    fn resolve_path(&self, mut path: Vec<usize>) -> NodeKey {
-        let mut node_key = self.root;
-
+    /// Returns `None` if the node doesn't exist anymore
+    fn resolve_path(&self, (mut node_key, mut path): NodePath) -> Option<NodeKey> {
         while let Some(index) = path.pop() {
-            node_key = self.view.first_child(node_key).unwrap();
+            let first_child = self.view.first_child(node_key)?;
+            node_key = first_child;
             for _ in 0..index {
                 node_key = self.view.next_sibling(node_key);
+                if node_key == first_child {
+                    return None;
+                }
             }
         }
 
-        node_key
+        Some(node_key)
     }
 
     /// Reload the view, allowing it to pick up state changes
+    ///
+    /// The focus, timers, animations and scroll offsets are kept
+    /// for nodes which are found at the same place in the new view.
     pub fn reload_view(&mut self) {
         let backup = &self.view[self.root];
         let factory = backup.factory;
         let xml_node_index = backup.xml_node_index;
-
-        let exf = self.explicit_focus.map(|nk| self.node_path(nk));
-        let imf = self.node_path(self.implicit_focus);
+        let paths = self.save_node_paths();
 
         self.view.reset(self.root);
         self.invalidate_layout();
-        let root_ns = self.namespaces.remove(&self.root).unwrap();
         self.namespaces.clear();
-        self.namespaces.insert(self.root, root_ns);
+        self.namespaces.insert(self.root, vec![root_ns(), tr_ns()]);
 
         let root = &mut self.view[self.root];
         root.factory = factory;
@@ -225,8 +289,293 @@ impl Application {
             self.call_populator(self.root, xml_root).unwrap();
         }
 
-        self.explicit_focus = exf.map(|p| self.resolve_path(p));
-        self.implicit_focus = self.resolve_path(imf);
+        for i in 0..self.overlays.len() {
+            let owner = self.resolve_path(self.overlays[i].owner_path.clone());
+            self.overlays[i].owner = owner.unwrap_or(self.root);
+
+            // keep the previous area, so that it's repainted if the content shrinks
+            let holder = self.overlays[i].holder;
+            let (position, size) = (self.view[holder].position, self.view[holder].size);
+            self.view.reset(holder);
+            self.view[holder].factory = Some(OVERLAY_MUTATOR_INDEX.into()).into();
+            self.view[holder].position = position;
+            self.view[holder].size = size;
+
+            self.populate_overlay(i).unwrap();
+        }
+
+        self.restore_node_paths(paths);
+    }
+
+    /// Saves the paths of the nodes which have the focus, timers,
+    /// animations or scroll offsets, before they're re-created
+    fn save_node_paths(&self) -> NodePaths {
+        let mut scrolled = Vec::new();
+        self.find_scrolled(self.root, &mut scrolled);
+        for i in 0..self.overlays.len() {
+            self.find_scrolled(self.overlays[i].holder, &mut scrolled);
+        }
+
+        NodePaths {
+            explicit_focus: self.explicit_focus.map(|nk| self.node_path(nk)),
+            implicit_focus: self.node_path(self.implicit_focus),
+            timers: self.timers.iter().map(|t| self.node_path(t.node_key)).collect(),
+            animations: self.animations.iter().map(|a| self.node_path(a.node_key)).collect(),
+            scrolled: scrolled.into_iter().map(|(nk, offset)| (self.node_path(nk), offset)).collect(),
+        }
+    }
+
+    /// Moves the focus, timers, animations and scroll offsets to
+    /// the nodes which are found at the same place in the new view
+    fn restore_node_paths(&mut self, paths: NodePaths) {
+        let explicit_focus = paths.explicit_focus.and_then(|p| self.resolve_path(p));
+        self.explicit_focus = explicit_focus.filter(|nk| !self.is_under_modal(*nk));
+        self.implicit_focus = self.resolve_path(paths.implicit_focus).unwrap_or(self.root);
+
+        // the layout applies (and clamps) restored scroll offsets
+        for (path, offset) in paths.scrolled {
+            if let Some(node_key) = self.resolve_path(path) {
+                self.view[node_key].scroll = offset;
+            }
+        }
+
+        // timers & animations of nodes which disappeared are dropped
+        let mut timers = core::mem::take(&mut self.timers).into_iter();
+        for (mut timer, path) in timers.by_ref().zip(paths.timers) {
+            if let Some(node_key) = self.resolve_path(path) {
+                timer.node_key = node_key;
+                self.timers.push(timer);
+            }
+        }
+
+        let mut animations = core::mem::take(&mut self.animations).into_iter();
+        for (mut animation, path) in animations.by_ref().zip(paths.animations) {
+            if let Some(node_key) = self.resolve_path(path) {
+                animation.node_key = node_key;
+                self.animations.push(animation);
+            }
+        }
+    }
+
+    /// Collects nodes whose children are scrolled, in a subtree
+    fn find_scrolled(&self, node_key: NodeKey, scrolled: &mut Vec<(NodeKey, SignedPixels)>) {
+        let offset = self.view[node_key].scroll;
+        if !offset.is_zero() {
+            scrolled.push((node_key, offset));
+        }
+
+        for_each_child!(self.view, node_key, child, {
+            self.find_scrolled(child, scrolled);
+        });
+    }
+
+    /// Deletes a node and its descendants
+    ///
+    /// If one of them had the explicit focus, it receives a
+    /// [`UserInputEvent::FocusLoss`]; the parent of the node
+    /// becomes the implicit focus if it was under the pointer.
+    pub fn delete_node(&mut self, node_key: NodeKey) -> Result<(), Error> {
+        self.unfocus(node_key)?;
+        self.view.delete(node_key);
+
+        Ok(())
+    }
+
+    /// Detaches a node and its descendants from the view, so that
+    /// they can be inserted elsewhere
+    ///
+    /// As only whole sibling lists can be unlinked from the tree, the
+    /// node is moved to a new key, which is returned; its descendants
+    /// keep their keys. The focus is handled as in [`Self::delete_node`].
+    pub(crate) fn detach_node(&mut self, node_key: NodeKey) -> Result<NodeKey, Error> {
+        self.unfocus(node_key)?;
+
+        let detached = self.view.create();
+        self.view[detached] = core::mem::take(&mut self.view[node_key]);
+
+        if let Some(children) = self.view.detach_children(node_key) {
+            self.view.append_children(children, detached);
+        }
+
+        if let Some(namespaces) = self.namespaces.remove(&node_key) {
+            self.namespaces.insert(detached, namespaces);
+        }
+
+        self.view.delete(node_key);
+
+        Ok(detached)
+    }
+
+    /// Moves the focus out of a node and its descendants
+    fn unfocus(&mut self, node_key: NodeKey) -> Result<(), Error> {
+        let contains = |app: &Self, mut current: NodeKey| loop {
+            if current == node_key {
+                break true;
+            }
+
+            match app.view.parent(current) {
+                Some(parent) => current = parent,
+                None => break false,
+            }
+        };
+
+        if let Some(focused) = self.explicit_focus {
+            if contains(self, focused) {
+                self.clear_focused_node()?;
+            }
+        }
+
+        if contains(self, self.implicit_focus) {
+            self.implicit_focus = self.view.parent(node_key).unwrap_or(self.root);
+        }
+
+        Ok(())
+    }
+
+    /// Opens an overlay above the main view and the previous overlays
+    ///
+    /// Its content is created from `xml_node_key`, which shouldn't be populated
+    /// elsewhere. `owner` is usually the node which opened the overlay: its JSON
+    /// state namespaces and style are available to the content. When the view is
+    /// reloaded, the content is re-created.
+    ///
+    /// Returns the holder of the overlay (see [`Overlay`]).
+    pub fn push_overlay(
+        &mut self,
+        owner: NodeKey,
+        xml_node_key: XmlNodeKey,
+        anchor: Anchor,
+        modal: bool,
+    ) -> Result<NodeKey, Error> {
+        if modal {
+            self.clear_focused_node()?;
+        }
+
+        let holder = self.view.create();
+        self.view[holder].factory = Some(OVERLAY_MUTATOR_INDEX.into()).into();
+
+        self.overlays.push(Overlay {
+            holder,
+            owner,
+            xml_node_index: xml_node_key.index(),
+            anchor,
+            modal,
+            owner_path: self.node_path(owner),
+        });
+
+        if modal {
+            // the node under the pointer isn't hovered anymore
+            let old = core::mem::replace(&mut self.implicit_focus, holder);
+            self.resize_hover_sensitive_nodes(old)?;
+        }
+
+        self.populate_overlay(self.overlays.len() - 1)?;
+
+        Ok(holder)
+    }
+
+    fn populate_overlay(&mut self, index: usize) -> Result<(), Error> {
+        let holder = self.overlays[index].holder;
+        let xml_node_index = self.overlays[index].xml_node_index;
+        let xml_node_key = self.xml_tree.node_key(xml_node_index);
+
+        let content = self.view.create();
+        self.view.append_children(content, holder);
+        self.view[content].xml_node_index = Some(xml_node_index).into();
+        self.view[content].factory = self.xml_tree[xml_node_key].factory;
+        self.invalidate_layout();
+
+        self.call_populator(content, xml_node_key)
+    }
+
+    /// Closes an overlay, and all the overlays above it
+    pub fn remove_overlay(&mut self, holder: NodeKey) -> Result<(), Error> {
+        let index = self.overlays.iter().position(|o| o.holder == holder);
+        let index = index.ok_or_else(|| error!("Node {:?} isn't an overlay holder", holder))?;
+
+        while self.overlays.len() > index {
+            let holder = self.overlays.last().unwrap().holder;
+
+            if let Some(focused) = self.explicit_focus {
+                if self.node_path(focused).0 == holder {
+                    self.clear_focused_node()?;
+                }
+            }
+
+            if self.node_path(self.implicit_focus).0 == holder {
+                self.implicit_focus = self.root;
+            }
+
+            let node = &self.view[holder];
+            self.damage.push((node.position, node.size));
+            self.view.delete(holder);
+            self.overlays.pop();
+        }
+
+        // the nodes under the removed overlays might now be hovered
+        self.set_focus_coords(self.focus_coords)
+    }
+
+    /// Returns the holder of the overlay containing a node, if any
+    pub fn overlay_of(&self, node_key: NodeKey) -> Option<NodeKey> {
+        let (layer, _) = self.node_path(node_key);
+        self.overlays.iter().find(|o| o.holder == layer).map(|o| o.holder)
+    }
+
+    /// The current overlays, from bottom to top
+    pub fn overlays(&self) -> &[Overlay] {
+        &self.overlays
+    }
+
+    /// Parent of a node in the view; the parent of an overlay holder is its owner.
+    fn logical_parent(&self, node_key: NodeKey) -> Option<NodeKey> {
+        match self.view.parent(node_key) {
+            Some(parent) => Some(parent),
+            None => self.overlays.iter().find(|o| o.holder == node_key).map(|o| o.owner),
+        }
+    }
+
+    /// Nodes under a modal overlay cannot be focused
+    fn is_under_modal(&self, node_key: NodeKey) -> bool {
+        let modal = match self.overlays.iter().rposition(|o| o.modal) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let (layer, _) = self.node_path(node_key);
+        !self.overlays[modal..].iter().any(|o| o.holder == layer)
+    }
+
+    fn compute_overlay_layouts(&mut self, fb_size: Size) -> Result<(), Error> {
+        for i in 0..self.overlays.len() {
+            let overlay = &self.overlays[i];
+            let (holder, modal) = (overlay.holder, overlay.modal);
+
+            let anchor = match overlay.anchor {
+                Anchor::Below => {
+                    let owner = &self.view[overlay.owner];
+                    Some(owner.position.add_size(Size::new(Pixels::ZERO, owner.size.h)))
+                },
+                Anchor::At(position) => Some(position),
+                Anchor::Center => None,
+            };
+
+            let old_rect = (self.view[holder].position, self.view[holder].size);
+            let content_rect = compute_overlay_layout(self, holder, anchor, fb_size)?;
+
+            let rect = match modal {
+                true => (Position::zero(), fb_size),
+                false => content_rect,
+            };
+
+            (self.view[holder].position, self.view[holder].size) = rect;
+            if rect != old_rect {
+                self.damage.push(old_rect);
+                self.view[holder].config.set_dirty(true);
+            }
+        }
+
+        Ok(())
     }
 
     /// Quick way to tell the application to recompute its layout before the next frame
@@ -234,6 +583,11 @@ impl Application {
         self.must_check_layout = true;
     }
 
+    /// Repaints an area in the next frame, e.g. one which a node just left
+    pub fn repaint(&mut self, rect: (Position, Size)) {
+        self.damage.push(rect);
+    }
+
     /// Read an asset from the internal cache
     pub fn get_asset(&self, asset: &ArcStr) -> Result<Rc<[u8]>, Error> {
         match self.assets.get(asset) {
@@ -314,7 +668,7 @@ impl Application {
         }?;
 
         let mut current = node_key;
-        while let Some(parent) = self.view.parent(current) {
+        while let Some(parent) = self.logical_parent(current) {
             if let Some(p_style) = self.view[parent].style_override.get() {
                 parent_style = p_style.into();
                 break;
@@ -326,6 +680,32 @@ impl Application {
         Ok(self.theme.get(parent_style))
     }
 
+    /// Makes a JSON state namespace available to a node and its descendants
+    ///
+    /// A node can create multiple namespaces; if two of them have
+    /// the same name, the last one hides the other.
+    pub fn add_namespace(&mut self, node: NodeKey, namespace: Namespace) {
+        match self.namespaces.get_mut(&node) {
+            Some(namespaces) => namespaces.push(namespace),
+            None => _ = self.namespaces.insert(node, vec![namespace]),
+        }
+    }
+
+    /// Finds the closest namespace named `ns_name`, and the node which created it
+    fn find_namespace(&self, node: NodeKey, ns_name: &str) -> Result<(NodeKey, &Namespace), Error> {
+        let mut target = node;
+        loop {
+            let namespaces = self.namespaces.get(&target).map(|n| n.iter().rev());
+            match namespaces.and_then(|mut n| n.find(|ns| &*ns.name == ns_name)) {
+                Some(ns) => break Ok((target, ns)),
+                None => match self.logical_parent(target) {
+                    Some(parent) => target = parent,
+                    None => break Err(error!("Missing {} namespace", ns_name)),
+                },
+            }
+        }
+    }
+
     /// Retrieves a value from the JSON state
     pub fn resolve(
         &self,
@@ -333,23 +713,99 @@ impl Application {
         ns_name: &str,
         ns_path: &str,
     ) -> Result<Path, Error> {
-        let mut target = node;
-        loop {
-            match self.namespaces.get(&target) {
-                Some(ns) if &*ns.name == ns_name => {
-                    let mut jp = ns.path.clone();
-                    (ns.callback)(&self, target, node, &mut jp)?;
-                    jp.append(parse_path(ns_path));
-                    break Ok(jp);
-                },
-                _ => match self.view.parent(target) {
-                    Some(parent) => target = parent,
-                    None => break Err(error!("Missing {} namespace", ns_name)),
-                },
-            }
+        let (target, ns) = self.find_namespace(node, ns_name)?;
+        let mut jp = ns.path.clone();
+        (ns.callback)(self, target, node, &mut jp)?;
+        jp.append(parse_path(ns_path));
+        Ok(jp)
+    }
+
+    /// Retrieves the current item of an iterating namespace
+    pub fn resolve_item(&self, node: NodeKey, ns_name: &str) -> Result<Item, Error> {
+        let (target, ns) = self.find_namespace(node, ns_name)?;
+        let mut jp = ns.path.clone();
+        match (ns.callback)(self, target, node, &mut jp)? {
+            Some(item) => Ok(item),
+            None => Err(error!("The {} namespace doesn't iterate", ns_name)),
+        }
+    }
+
+    /// Retrieves `@index` or `@key` from the current item of a namespace
+    fn item_property(&self, node: NodeKey, ns_name: &str, property: &str) -> Result<ArcStr, Error> {
+        let item = self.resolve_item(node, ns_name)?;
+        match (property, item.key) {
+            ("key", Some(key)) => Ok(key),
+            ("key" | "index", _) => Ok(crate::format!("{}", item.index).into()),
+            _ => Err(error!("Unknown item property: {}:@{}", ns_name, property)),
         }
     }
 
+    /// Adds the mutators of a third-party library
+    ///
+    /// In XML layouts, their tags must be prefixed with the prefix of
+    /// the library: `<charts:line>`. This should be called before the
+    /// layout is parsed, i.e. right after [`Self::new`].
+    pub fn add_library(&mut self, library: MutatorLibrary) -> Result<(), Error> {
+        if self.libraries.iter().any(|(prefix, _)| *prefix == library.prefix) {
+            return Err(error!("A mutator library already uses this prefix: {}", library.prefix));
+        }
+
+        let range = self.mutators.len()..self.mutators.len() + library.mutators.len();
+        self.mutators.extend_from_slice(library.mutators);
+        self.libraries.push((library.prefix, range.clone()));
+
+        for i in range {
+            (self.mutators[i].handlers.initializer)(self, i.into())?;
+        }
+
+        Ok(())
+    }
+
+    /// Registers the name of a parsed XML file, see [`XmlNode::file`](super::xml::XmlNode::file)
+    pub fn add_source_file(&mut self, name: &ArcStr) -> FileIndex {
+        self.source_files.push(name.clone());
+        (self.source_files.len() - 1).into()
+    }
+
+    /// Retrieves the name of a parsed XML file
+    pub fn source_file(&self, file: FileIndex) -> Option<&ArcStr> {
+        self.source_files.get(usize::from(file))
+    }
+
+    /// Location of an XML node in its file: `file:line:column`
+    pub fn xml_location(&self, xml_node_key: XmlNodeKey) -> String {
+        let xml_node = &self.xml_tree[xml_node_key];
+        let file = xml_node.file.get().and_then(|file| self.source_file(file));
+        let line = xml_node.line.get().map(usize::from).unwrap_or(0);
+        let column = xml_node.column.get().map(usize::from).unwrap_or(0);
+
+        crate::format!("{}:{}:{}", file.map_or("<unknown>", |file| &**file), line, column)
+    }
+
+    /// Location of the XML node from which a node was created, see [`Self::xml_location`]
+    pub fn node_location(&self, node: NodeKey) -> String {
+        match self.view[node].xml_node_index.get() {
+            Some(index) => self.xml_location(self.xml_tree.node_key(index)),
+            None => "<subnode>".into(),
+        }
+    }
+
+    /// Prefixes an error with the location & name of an attribute
+    fn attr_error(&self, node: NodeKey, attr: usize, error: Error) -> Error {
+        let xml_node_key = match self.view[node].xml_node_index.get() {
+            Some(index) => self.xml_tree.node_key(index),
+            None => return error,
+        };
+
+        let mutator = self.view[node].factory.get().map(|i| &self.mutators[usize::from(i)]);
+        let name = match mutator.and_then(|m| m.xml_params.as_ref()) {
+            Some(params) => params.attr_set.get(attr).map_or("?", |a| a.0),
+            None => "?",
+        };
+
+        locate(error, &crate::format!("{}: {}", self.xml_location(xml_node_key), name))
+    }
+
     /// Retrieves the XML tag name of a node
     ///
     /// This can return the following special strings:
@@ -367,18 +823,30 @@ impl Application {
         }
     }
 
-    #[doc(hidden)]
-    pub fn attr_state_path(&mut self, node: NodeKey, attr: usize) -> Result<Result<(Path, AttributeValueType), AttributeValue>, Error> {
+    /// Returns the namespace, path & type of an attribute if it's a JSON state lookup
+    fn state_lookup(&self, node: NodeKey, attr: usize) -> Result<(ArcStr, ArcStr, AttributeValueType), AttributeValue> {
         let xml_node_index = self.view[node].xml_node_index.get()
             .expect("cannot use Application::attr on nodes without xml_node_index");
         let xml_node_key = self.xml_tree.node_key(xml_node_index);
         let xml_node = &self.xml_tree[xml_node_key];
 
-        let (namespace, path, value_type) = match xml_node.attributes.get(attr).clone() {
-            AttributeValue::StateLookup { namespace, path, value_type } => (namespace, path, value_type),
-            value => return Ok(Err(value)),
+        match xml_node.attributes.get(attr).clone() {
+            AttributeValue::StateLookup { namespace, path, value_type } => Ok((namespace, path, value_type)),
+            value => Err(value),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn attr_state_path(&mut self, node: NodeKey, attr: usize) -> Result<Result<(Path, AttributeValueType), AttributeValue>, Error> {
+        let (namespace, path, value_type) = match self.state_lookup(node, attr) {
+            Ok(tuple) => tuple,
+            Err(value) => return Ok(Err(value)),
         };
 
+        if path.starts_with('@') || &*namespace == "tr" {
+            return Err(error!("{}:{} is read-only", namespace, path));
+        }
+
         Ok(Ok((self.resolve(node, &namespace, path.deref())?, value_type)))
     }
 
@@ -399,19 +867,63 @@ impl Application {
     /// Here, the `text` attribute will contain the value of the JSON state at `some` / `json` / `path` / `items` / fourth item.
     ///
     /// `root` specifies the main JSON state namespace. Use [Iterating Containers](http://todo.io/) to create other ones.
+    ///
+    /// ## Item properties
+    ///
+    /// `<label item:text="@index" />`
+    ///
+    /// In an iterating container, `@index` is the position of the current
+    /// item and `@key` its key (or its position, when iterating over an array).
+    ///
+    /// ## Interpolation
+    ///
+    /// `<label text="Hello {root:user.name|upper}" />`
+    ///
+    /// Lookups between braces are replaced by their values, optionally
+    /// transformed by [formatters](super::interpolation::Formatter).
+    ///
+    /// Errors are prefixed with the location of the XML node & the name of the attribute.
     pub fn attr<T: TryFrom<AttributeValue, Error=Error>>(
         &mut self,
         node: NodeKey,
         attr: usize,
+    ) -> Result<T, Error> {
+        self.attr_value(node, attr).map_err(|e| self.attr_error(node, attr, e))
+    }
+
+    fn attr_value<T: TryFrom<AttributeValue, Error=Error>>(
+        &mut self,
+        node: NodeKey,
+        attr: usize,
     ) -> Result<T, Error> {
         use AttributeValueType::*;
 
-        let (json_path, value_type) = match self.attr_state_path(node, attr)? {
+        let (namespace, path, value_type) = match self.state_lookup(node, attr) {
             Ok(tuple) => tuple,
+            Err(AttributeValue::Interpolation { interpolation, value_type }) => {
+                let string = self.interpolate(node, &interpolation)?;
+                return T::try_from(AttributeValue::parse_literal(&string.into(), value_type)?);
+            },
             Err(value) => return T::try_from(value),
         };
 
-        let value = match (&self.state[&json_path], value_type) {
+        if &*namespace == "tr" {
+            let string = self.translate(node, &path)?;
+            return T::try_from(AttributeValue::parse_literal(&string.into(), value_type)?);
+        }
+
+        // item properties are strings, which are parsed below
+        let property;
+        let mut json_path = None;
+        let value = match path.strip_prefix('@') {
+            Some(name) => {
+                property = Value::String(self.item_property(node, &namespace, name)?);
+                &property
+            },
+            None => &self.state[json_path.insert(self.resolve(node, &namespace, &path)?)],
+        };
+
+        let value = match (value, value_type) {
             // String dumps:
             (
                 Value::Array  (_) |
@@ -422,7 +934,7 @@ impl Application {
 
                 Other | OptOther,
             ) => {
-                let string = self.state.dump(&json_path)
+                let string = self.state.dump(json_path.as_ref().unwrap())
                     .map_err(|e| error!("unexpected fmt error: {:?}", e))?;
 
                 match value_type {
@@ -432,8 +944,21 @@ impl Application {
                 }
             },
 
+            // Numbers:
+            (
+                Value::Number(n),
+
+                SignedPixels | Pixels | Ratio | Number |
+                OptSignedPixels | OptPixels | OptRatio | OptNumber,
+            ) => AttributeValue::from_number(*n, value_type)?,
+
+            // Booleans:
+            (Value::Boolean(b), Boolean) => AttributeValue::Boolean(*b),
+            (Value::Boolean(b), OptBoolean) => AttributeValue::OptBoolean(Some(*b)),
+            (Value::Null, OptBoolean) => AttributeValue::OptBoolean(None),
+
             // Common conversions:
-            (Value::String(s), _) => AttributeValue::parse(&s, value_type)?,
+            (Value::String(s), _) => AttributeValue::parse_literal(s, value_type)?,
 
             _ => return Err(error!("Invalid Attribute Conversion")),
         };
@@ -443,6 +968,222 @@ impl Application {
         T::try_from(value)
     }
 
+    /// Replaces the lookups of an interpolation with their formatted values
+    pub fn interpolate(&self, node: NodeKey, interpolation: &Interpolation) -> Result<String, Error> {
+        let mut string = String::new();
+
+        for segment in &interpolation.segments {
+            let (namespace, path, formatters) = match segment {
+                Segment::Text(text) => {
+                    string.push_str(text);
+                    continue;
+                },
+                Segment::Lookup { namespace, path, formatters } => (namespace, path, formatters),
+            };
+
+            let mut value = match path.strip_prefix('@') {
+                Some(name) => Value::String(self.item_property(node, namespace, name)?),
+                None if &**namespace == "tr" => Value::String(self.translate(node, path)?.into()),
+                None => {
+                    let json_path = self.resolve(node, namespace, path)?;
+                    match &self.state[&json_path] {
+                        Value::Array(_) | Value::Object(_) => {
+                            let dump = self.state.dump(&json_path)
+                                .map_err(|e| error!("unexpected fmt error: {:?}", e))?;
+                            Value::String(dump)
+                        },
+                        value => value.clone(),
+                    }
+                },
+            };
+
+            for formatter in formatters {
+                value = formatter.apply(value)?;
+            }
+
+            string.push_str(&display(&value));
+        }
+
+        Ok(string)
+    }
+
+    /// Retrieves a message from the translation tables, for the current locale
+    ///
+    /// `message` is a message ID, optionally followed by a lookup
+    /// of the number which selects the plural form of the message:
+    /// `inbox.unread(root:unread-count)`. Messages can contain
+    /// interpolated lookups, which are resolved from `node`.
+    pub fn translate(&self, node: NodeKey, message: &str) -> Result<String, Error> {
+        let (id, count) = match message.strip_suffix(')').and_then(|m| m.split_once('(')) {
+            Some((id, count)) => (id.trim(), Some(count.trim())),
+            None => (message.trim(), None),
+        };
+
+        let count = match count {
+            Some(count) => {
+                let (ns_name, path) = count.split_once(':')
+                    .ok_or_else(|| error!("Missing namespace in {}", count))?;
+
+                let number = match path.strip_prefix('@') {
+                    Some(name) => self.item_property(node, ns_name, name)?.parse().ok(),
+                    None => match &self.state[&self.resolve(node, ns_name, path)?] {
+                        Value::Number(number) => Some(*number),
+                        _ => None,
+                    },
+                };
+
+                Some(number.ok_or_else(|| error!("{} is not a number", count))?)
+            },
+            None => None,
+        };
+
+        let path = self.resolve(node, "tr", id)?;
+        let message = self.localization.message(&path, count)
+            .ok_or_else(|| error!("Missing translation: {} (locale: {})", id, self.localization.locale))?;
+
+        self.interpolate(node, &Interpolation::parse(&message)?)
+    }
+
+    /// Current locale of the app; the default is `en`
+    pub fn locale(&self) -> &str {
+        &self.localization.locale
+    }
+
+    /// Changes the locale of the app, updating nodes which display translated messages
+    ///
+    /// Messages missing from the translation table of the locale are
+    /// searched in the table of its language (`fr` for `fr-CA`), then
+    /// in that of the fallback locale.
+    pub fn set_locale(&mut self, locale: &str) -> Result<(), Error> {
+        self.localization.locale = locale.into();
+        self.refresh_translations()
+    }
+
+    /// Sets the locale which provides missing messages
+    pub fn set_fallback_locale(&mut self, locale: Option<&str>) -> Result<(), Error> {
+        self.localization.fallback = locale.map(ArcStr::from);
+        self.refresh_translations()
+    }
+
+    /// Adds the translation table of a locale, see [`Localization`]
+    ///
+    /// If the app already has a table for this locale, it's replaced.
+    pub fn add_translations(&mut self, locale: &str, json: &[u8]) -> Result<(), Error> {
+        let json = from_utf8(json).map_err(|_| error!("Translation table for {} isn't valid UTF-8", locale))?;
+        self.localization.add_table(locale, json)?;
+        self.refresh_translations()
+    }
+
+    /// Re-creates the nodes which have `tr:` lookups in their attributes
+    fn refresh_translations(&mut self) -> Result<(), Error> {
+        let mut layers = vec![self.root];
+        layers.extend(self.overlays.iter().map(|o| o.holder));
+
+        for layer in layers {
+            self.refresh_namespace(layer, "tr")?;
+        }
+
+        Ok(())
+    }
+
+    /// Re-creates the nodes of a subtree which have lookups in
+    /// the `ns_name` namespace in their attributes
+    ///
+    /// Nodes displaying a branch of a conditional are re-created from
+    /// the conditional, so that its condition is evaluated again.
+    ///
+    /// As in [`Self::reload_view`], the focus, timers, animations and
+    /// scroll offsets are kept for nodes found at the same place.
+    pub(crate) fn refresh_namespace(&mut self, node: NodeKey, ns_name: &str) -> Result<(), Error> {
+        if let Some(xml_node_index) = self.view[node].xml_node_index.get() {
+            let xml_node_key = self.xml_tree.node_key(xml_node_index);
+
+            if uses_namespace(self, xml_node_key, ns_name) {
+                let xml_node_key = conditional_source(self, xml_node_key);
+                let factory = self.xml_tree[xml_node_key].factory;
+                let rect = (self.view[node].position, self.view[node].size);
+                let paths = self.save_node_paths();
+
+                // the populators will create them again
+                self.forget_namespaces(node);
+                if node == self.root {
+                    self.namespaces.insert(self.root, vec![root_ns(), tr_ns()]);
+                }
+
+                self.view.reset(node);
+                self.view[node].factory = factory;
+                self.view[node].xml_node_index = Some(xml_node_key.index()).into();
+                self.repaint(rect);
+                self.invalidate_layout();
+
+                let result = self.call_populator(node, xml_node_key);
+                self.restore_node_paths(paths);
+                return result;
+            }
+        }
+
+        for_each_child!(self.view, node, child, {
+            self.refresh_namespace(child, ns_name)?;
+        });
+
+        Ok(())
+    }
+
+    /// Removes the namespaces created by a node and its descendants
+    fn forget_namespaces(&mut self, node: NodeKey) {
+        self.namespaces.remove(&node);
+
+        for_each_child!(self.view, node, child, {
+            self.forget_namespaces(child);
+        });
+    }
+
+    /// Evaluates an attribute as a condition, as in `<if test="...">`
+    ///
+    /// Immediate values must be `true` or `false`; the attribute must be
+    /// typed as a boolean or as text. JSON state lookups
+    /// use the syntax of [`Filter`]: `root:test="!path"` or
+    /// `item:test="@index == 0"` for instance.
+    pub fn attr_condition(&self, node: NodeKey, attr: usize) -> Result<bool, Error> {
+        self.condition_value(node, attr).map_err(|e| self.attr_error(node, attr, e))
+    }
+
+    fn condition_value(&self, node: NodeKey, attr: usize) -> Result<bool, Error> {
+        let (namespace, path) = match self.state_lookup(node, attr) {
+            Ok((namespace, path, _)) => (namespace, path),
+            Err(AttributeValue::Other(value)) => return match value.parse() {
+                Ok(boolean) => Ok(boolean),
+                Err(_) => Err(error!("Invalid condition: {} (expected true or false)", value)),
+            },
+            Err(AttributeValue::Interpolation { interpolation, .. }) => {
+                let value = self.interpolate(node, &interpolation)?;
+                return match value.parse() {
+                    Ok(boolean) => Ok(boolean),
+                    Err(_) => Err(error!("Invalid condition: {} (expected true or false)", value)),
+                };
+            },
+            Err(AttributeValue::Boolean(boolean)) => return Ok(boolean),
+            Err(AttributeValue::OptBoolean(boolean)) => return Ok(boolean.unwrap_or(false)),
+            Err(value) => return Err(error!("Invalid condition: {:?} (expected true or false)", value)),
+        };
+
+        let filter = Filter::parse(&path);
+        let property;
+        let json_path;
+        let value = match filter.path.strip_prefix('@') {
+            Some(name) => {
+                property = Value::String(self.item_property(node, &namespace, name)?);
+                &property
+            },
+            None => {
+                json_path = self.resolve(node, &namespace, filter.path)?;
+                &self.state[&json_path]
+            },
+        };
+
+        Ok(filter.matches(value))
+    }
+
     fn build_render_list(&mut self, fb_rect: &(Position, Size), key: NodeKey, querying: bool) {
         // let _tag = self.xml_tag(key);
         let node = &mut self.view[key];
@@ -488,11 +1229,15 @@ impl Application {
             self.view[key].background.paint(fb, texture_coords, sampling_window, stride, true, false);
         }
 
+        let mut children_restrict = match self.view[key].overflow {
+            Overflow::Visible => backup,
+            _ => *restrict,
+        };
+
         for_each_child!(self.view, key, child, {
-            self.paint(child, fb, stride, restrict)?;
+            self.paint(child, fb, stride, &mut children_restrict)?;
         });
 
-
         for sampling_window in &self.render_list {
             let mut sampling_window = *sampling_window;
             constrain(&texture_coords, &mut sampling_window);
@@ -504,13 +1249,41 @@ impl Application {
             self.view[key].foreground.paint(fb, texture_coords, sampling_window, stride, true, false);
         }
 
+        if let Some(scrollbar) = get_scrollbar(self, key) {
+            let style = match self.view[key].style_override.get() {
+                Some(style) => self.theme.get(style.into()),
+                None => self.get_inherited_style(key)?,
+            };
+
+            let thumb = PixelSource::SolidColor(style.outline);
+            for sampling_window in &self.render_list {
+                let mut sampling_window = *sampling_window;
+                constrain(&scrollbar.thumb, &mut sampling_window);
+                constrain(restrict, &mut sampling_window);
+                thumb.paint(fb, scrollbar.thumb, sampling_window, stride, true, false);
+            }
+        }
+
         *restrict = backup;
 
         Ok(())
     }
 
-    /// Alias of [`hit_test`]
+    /// Finds the node at some position, in overlays first, then in the main view
+    ///
+    /// Modal overlays capture all positions. See [`hit_test`].
     pub fn hit_test(&self, position: Position) -> NodeKey {
+        for overlay in self.overlays.iter().rev() {
+            let node = &self.view[overlay.holder];
+            let bottom_right = node.position.add_size(node.size);
+            let x_in_range = node.position.x <= position.x && bottom_right.x > position.x;
+            let y_in_range = node.position.y <= position.y && bottom_right.y > position.y;
+
+            if overlay.modal || (x_in_range && y_in_range) {
+                return hit_test(&self.view, overlay.holder, position);
+            }
+        }
+
         hit_test(&self.view, self.root, position)
     }
 
@@ -531,6 +1304,127 @@ impl Application {
         Ok(())
     }
 
+    /// Updates the time elapsed since the application started,
+    /// then calls expired timers and running animations.
+    ///
+    /// Platforms should call this before rendering each frame.
+    pub fn set_age(&mut self, age: Duration) -> Result<(), Error> {
+        self.age = age;
+
+        let mut expired = Vec::new();
+        let mut i = 0;
+        while let Some(timer) = self.timers.get(i) {
+            match timer.deadline <= age {
+                true => expired.push(self.timers.swap_remove(i)),
+                false => i += 1,
+            }
+        }
+
+        // expired timers are all called, even if one of them fails;
+        // the first error is returned afterwards.
+        let mut result = Ok(());
+
+        expired.sort_by_key(|timer| timer.deadline);
+        for timer in expired {
+            // a previous callback might have reloaded the view
+            if self.view.get(timer.node_key).is_some() {
+                let called = (timer.callback)(self, timer.node_key);
+                result = result.and(called);
+            }
+        }
+
+        // callbacks can start new animations, which are kept in `self.animations`
+        for animation in core::mem::take(&mut self.animations) {
+            let node_key = animation.node_key;
+            if self.view.get(node_key).is_none() {
+                continue;
+            }
+
+            let elapsed = age.saturating_sub(animation.start);
+            let running = match (animation.callback)(self, node_key, elapsed) {
+                Ok(running) => running,
+                Err(error) => {
+                    result = result.and(Err(error));
+                    false
+                },
+            };
+
+            if let Some(node) = self.view.get_mut(node_key) {
+                node.config.set_dirty(true);
+                if running {
+                    self.animations.push(animation);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Time elapsed since the application started, as set by the platform
+    pub fn age(&self) -> Duration {
+        self.age
+    }
+
+    /// Calls `callback` once, after `delay`
+    ///
+    /// The timer is cancelled if `node_key` disappears when the view is reloaded.
+    pub fn set_timer(&mut self, node_key: NodeKey, delay: Duration, callback: TimerCallback) -> TimerId {
+        let id = TimerId(self.next_timer_id);
+        self.next_timer_id += 1;
+
+        self.timers.push(Timer {
+            id,
+            node_key,
+            deadline: self.age + delay,
+            callback,
+        });
+
+        id
+    }
+
+    /// Cancels a pending timer; returns false if it already expired.
+    pub fn cancel_timer(&mut self, id: TimerId) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        len != self.timers.len()
+    }
+
+    /// Calls `callback` before each frame, until it returns `false`
+    ///
+    /// The animation is stopped if `node_key` disappears when the view is reloaded.
+    pub fn animate(&mut self, node_key: NodeKey, callback: AnimationCallback) {
+        self.animations.push(Animation {
+            node_key,
+            start: self.age,
+            callback,
+        });
+    }
+
+    /// Stops all animations of a node
+    ///
+    /// Animation callbacks should return `false` instead of calling this on their own node.
+    pub fn stop_animations(&mut self, node_key: NodeKey) {
+        self.animations.retain(|animation| animation.node_key != node_key);
+    }
+
+    /// Tells the platform when the next frame should be rendered
+    ///
+    /// - `Some(Duration::ZERO)` if animations are running, assets are requested
+    ///   or the layout must be updated,
+    /// - the time until the next timer expires if there's one,
+    /// - `None` otherwise: the application is idle.
+    ///
+    /// In any case, platforms should render a new frame after delivering user input
+    /// or assets to the application.
+    pub fn next_frame(&self) -> Option<Duration> {
+        if !self.animations.is_empty() || !self.requests.is_empty() || self.must_check_layout {
+            return Some(Duration::ZERO);
+        }
+
+        let next_deadline = self.timers.iter().map(|timer| timer.deadline).min();
+        next_deadline.map(|deadline| deadline.saturating_sub(self.age))
+    }
+
     /// Returns true if the current focus coords is over a node.
     pub fn is_hovered(&self, node_key: NodeKey) -> bool {
         let mut current = Some(self.implicit_focus);
@@ -550,13 +1444,14 @@ impl Application {
         let new = self.hit_test(focus_coords);
         self.implicit_focus = new;
 
+        self.focus_coords = focus_coords;
+
         if old != new {
             self.resize_hover_sensitive_nodes(old)?;
             self.resize_hover_sensitive_nodes(new)?;
+            hover_changed(self)?;
         }
 
-        self.focus_coords = focus_coords;
-
         Ok(())
     }
 
@@ -566,11 +1461,26 @@ impl Application {
     ///
     /// The explicitly focused node will receive a FocusLoss event
     /// when it loses focus.
+    ///
+    /// Nodes under a modal overlay cannot grab focus.
     pub fn set_focused_node(&mut self, node_key: NodeKey) -> Result<(), Error> {
+        if self.is_under_modal(node_key) {
+            log::warn!("Cannot focus a node under a modal overlay");
+            return Ok(());
+        }
+
         self.clear_focused_node()?;
         self.explicit_focus = Some(node_key);
 
-        Ok(())
+        self.scroll_into_view(node_key)
+    }
+
+    /// Scrolls the ancestors of a node so that it becomes visible
+    ///
+    /// Only containers with `overflow="scroll"` are scrolled. This
+    /// is called when a node is focused with [`Self::set_focused_node`].
+    pub fn scroll_into_view(&mut self, node_key: NodeKey) -> Result<(), Error> {
+        scroll_into_view(self, node_key)
     }
 
     /// Unsets the current explicit focus
@@ -594,6 +1504,11 @@ impl Application {
         self.explicit_focus
     }
 
+    /// Takes the text copied by the last [`UserInputEvent::TextCopy`], if any
+    pub fn take_clipboard(&mut self) -> Option<ArcStr> {
+        self.clipboard.take()
+    }
+
     /// Renders the current view in a `framebuffer`.
     ///
     /// This expects the framebuffer to keep its content between calls.
@@ -635,12 +1550,23 @@ impl Application {
 
         if self.must_check_layout && !self.debug.freeze_layout {
             log::warn!("recomputing layout");
-            compute_layout(self, self.root)?;
+            // resizers may invalidate the layout again
             self.must_check_layout = false;
+            compute_layout(self, self.root)?;
+            self.compute_overlay_layouts(new_size)?;
         }
 
         if self.render_list.len() == 0 {
+            for mut rect in self.damage.drain(..) {
+                constrain(&fb_rect, &mut rect);
+                self.render_list.push(rect);
+            }
+
             self.build_render_list(&fb_rect, self.root, true);
+            for i in 0..self.overlays.len() {
+                self.build_render_list(&fb_rect, self.overlays[i].holder, true);
+            }
+
             let mut dirty_pixels = 0;
 
             for rect in &self.render_list {
@@ -656,8 +1582,15 @@ impl Application {
         if self.render_list.len() > 0 {
             let mut restrict = fb_rect;
             self.paint(self.root, framebuffer, stride, &mut restrict)?;
+
+            for i in 0..self.overlays.len() {
+                let mut restrict = fb_rect;
+                self.paint(self.overlays[i].holder, framebuffer, stride, &mut restrict)?;
+            }
         }
 
+        self.damage.clear();
+
         Ok(&self.render_list)
     }
 }
@@ -691,7 +1624,19 @@ impl Application {
         }
     }
 
+    pub fn call_scroller(&mut self, node_key: NodeKey) -> Result<(), Error> {
+        match self.view[node_key].factory.get() {
+            Some(i) => (self.mutators[usize::from(i)].handlers.scroller)(self, i, node_key),
+            None => Ok(()),
+        }
+    }
+
     pub fn call_user_input_handler(&mut self, target: NodeKey, event: &UserInputEvent) -> Result<bool, Error> {
+        // nodes under a modal overlay only receive FocusLoss
+        if *event != UserInputEvent::FocusLoss && self.is_under_modal(target) {
+            return Ok(false);
+        }
+
         let mut node_key = target;
         loop {
             match self.view[node_key].factory.get() {
@@ -710,3 +1655,21 @@ impl Application {
         }
     }
 }
+
+/// Creates an application displaying `xml` in a 200x100 framebuffer,
+/// with `state` as its JSON state
+#[cfg(test)]
+pub(crate) fn test_app(state: &str, xml: &str) -> Application {
+    let mut app = Application::new("main.xml".into(), SimpleCallbackMap::new());
+    app.state = JsonFile::new(Some(state)).unwrap();
+    app.data_response("main.xml".into(), xml.as_bytes().into()).unwrap();
+    test_render(&mut app);
+    app
+}
+
+/// Computes the layout of a test application and renders a frame
+#[cfg(test)]
+pub(crate) fn test_render(app: &mut Application) {
+    let mut framebuffer = vec![RGBA8::new(0, 0, 0, 0); 200 * 100];
+    app.render((200, 100), &mut framebuffer).unwrap();
+}
@@ -0,0 +1,41 @@
+//! Timers & Animations
+
+use super::node::NodeKey;
+use super::app::Application;
+use crate::Error;
+use core::time::Duration;
+
+/// Called once when a timer expires
+///
+/// `node_key` is the node which was passed to [`Application::set_timer`].
+pub type TimerCallback = fn(
+    app: &mut Application,
+    node_key: NodeKey,
+) -> Result<(), Error>;
+
+/// Called before each frame while an animation runs
+///
+/// `elapsed` is the time since the animation was started. The node is
+/// marked dirty after each call. Return `false` to stop the animation.
+pub type AnimationCallback = fn(
+    app: &mut Application,
+    node_key: NodeKey,
+    elapsed: Duration,
+) -> Result<bool, Error>;
+
+/// Handle to a pending timer, see [`Application::cancel_timer`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimerId(pub(crate) usize);
+
+pub(crate) struct Timer {
+    pub(crate) id: TimerId,
+    pub(crate) node_key: NodeKey,
+    pub(crate) deadline: Duration,
+    pub(crate) callback: TimerCallback,
+}
+
+pub(crate) struct Animation {
+    pub(crate) node_key: NodeKey,
+    pub(crate) start: Duration,
+    pub(crate) callback: AnimationCallback,
+}
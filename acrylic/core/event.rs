@@ -67,6 +67,22 @@ pub type Resizer = fn(
     node_key: NodeKey,
 ) -> Result<(), Error>;
 
+/// Reacts to a change of the visible part of a node's content
+///
+/// Called after the layout placed the children of the node,
+/// and after they were scrolled (see [`scroll`]).
+///
+/// # Arguments
+///
+/// - `node_key`: The node whose children were placed or scrolled
+///
+/// [`scroll`]: super::layout::scroll
+pub type Scroller = fn(
+    app: &mut Application,
+    m: MutatorIndex,
+    node_key: NodeKey,
+) -> Result<(), Error>;
+
 /// Processes user input
 ///
 /// # Arguments
@@ -94,6 +110,7 @@ pub struct Handlers {
     pub populator: Populator,
     pub finalizer: Finalizer,
     pub resizer: Resizer,
+    pub scroller: Scroller,
     pub user_input_handler: UserInputHandler,
 }
 
@@ -117,6 +134,10 @@ fn resizer(_app: &mut Application, _m: MutatorIndex, _: NodeKey) -> Result<(), E
     Ok(())
 }
 
+fn scroller(_app: &mut Application, _m: MutatorIndex, _: NodeKey) -> Result<(), Error> {
+    Ok(())
+}
+
 fn user_input_handler(_: &mut Application, _: MutatorIndex, _: NodeKey, _: NodeKey, _: &UserInputEvent) -> Result<bool, Error> {
     Ok(false)
 }
@@ -125,6 +146,7 @@ fn user_input_handler(_: &mut Application, _: MutatorIndex, _: NodeKey, _: NodeK
 ///
 /// - `initializer`: does nothing
 /// - `resizer`: does nothing
+/// - `scroller`: does nothing
 /// - `user_input_handler`: does nothing, returns false
 /// - `parser`: returns an error
 /// - `populator`: returns an error
@@ -135,6 +157,7 @@ pub const DEFAULT_HANDLERS: Handlers = Handlers {
     populator,
     finalizer,
     resizer,
+    scroller,
     user_input_handler,
 };
 
@@ -167,6 +190,19 @@ pub enum UserInputEvent<'a> {
     /// the offset is a byte offset (todo: make this a char offset);
     /// A value of zero means nothing is deleted.
     TextDelete(isize),
+    /// Copy the current content; the platform can then
+    /// retrieve it with [`Application::take_clipboard`].
+    ///
+    /// [`Application::take_clipboard`]: crate::core::app::Application::take_clipboard
+    TextCopy,
+    /// The pointer moved while its primary button was pressed;
+    /// see [`Application::get_focus_coords`] for its position.
+    Drag,
+    /// The primary button was released after [`UserInputEvent::Drag`] events
+    DragEnd,
+    /// The user pressed Enter (or equivalent)
+    /// while this node had the focus
+    Activate,
     /// User unselected this node
     ///
     /// Set app.focused to a nodekey to grab focus
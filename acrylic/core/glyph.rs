@@ -2,13 +2,13 @@
 //!
 //! todo: implement <https://steamcdn-a.akamaihd.net/apps/valve/2007/SIGGRAPH2007_AlphaTestedMagnification.pdf>
 
-use crate::{Error, Vec, Box, HashMap, LiteMap, ArcStr, ro_string, Rc, TEXT_SSAA, TEXT_SSAA_SQ};
+use crate::{Error, Vec, vec, Box, HashMap, LiteMap, ArcStr, ro_string, Rc, TEXT_SSAA, TEXT_SSAA_SQ};
 use super::visual::{RgbaPixelArray, GrayScalePixelArray, PixelSource, SignedPixels};
 use super::app::{Application, FONT_MUTATOR_INDEX};
 use super::node::{NodeKey, Mutator, MutatorIndex};
 use super::event::{Handlers, DEFAULT_HANDLERS};
 use core::{fmt::{self, Write}};
-use super::text_edit::Cursor;
+use super::text_edit::{Cursor, SOFT_HYPHEN};
 use super::rgb::RGBA8;
 
 use ttf_parser::{Tag, Face, OutlineBuilder};
@@ -98,8 +98,8 @@ impl Font {
 
     /// Shorthand for the following code:
     ///
-    /// ```rust
-    /// let mut renderer = font.renderer(None, font_size);
+    /// ```rust,ignore
+    /// let mut renderer = font.renderer(None, None, font_size);
     /// renderer.write(text);
     /// renderer.width()
     /// ```
@@ -217,6 +217,10 @@ impl<'a> GlyphRenderer<'a> {
                 continue;
             }
 
+            if glyph == SOFT_HYPHEN {
+                continue;
+            }
+
             let (advance, side_bearing, _) = self.extract_glyph(glyph, None);
 
             if APPLY_SIDE_BEARING && self.width > side_bearing {
@@ -251,7 +255,7 @@ impl<'a> GlyphRenderer<'a> {
             for glyph in text.chars() {
                 if glyph.is_whitespace() {
                     // lifetime trick
-                    let (pixels, color) = &mut self.render_data.as_mut().unwrap();
+                    let (pixels, _) = &mut self.render_data.as_mut().unwrap();
 
                     let advance = space_width(self.font_size);
                     let mut px_offset = cursor * 4;
@@ -260,23 +264,19 @@ impl<'a> GlyphRenderer<'a> {
                         px_offset += new_line_len;
                     }
 
-                    if has_cursor(&self.cursors, self.char_pos) {
-                        let fake_fb = pixels.as_rgba_mut();
-                        let mut dst_offset = cursor;
-                        for _ in 0..self.font_size {
-                            for x in 0..CURSOR_WIDTH {
-                                let dst = &mut fake_fb[dst_offset + x];
-                                *dst = *color;
-                            }
-                            dst_offset += self.width;
-                        }
-                    }
+                    self.draw_cursor(cursor);
 
                     cursor += advance;
                     self.char_pos += 1;
                     continue;
                 }
 
+                if glyph == SOFT_HYPHEN {
+                    self.draw_cursor(cursor);
+                    self.char_pos += 1;
+                    continue;
+                }
+
                 let (advance, side_bearing, glyph_mask) = self.extract_glyph(glyph, None);
 
                 if APPLY_SIDE_BEARING && cursor > side_bearing {
@@ -304,35 +304,26 @@ impl<'a> GlyphRenderer<'a> {
                     src_offset += advance;
                 }
 
-                if has_cursor(&self.cursors, self.char_pos) {
-                    let mut dst_offset = 0;
-                    for _ in 0..self.font_size {
-                        for x in 0..CURSOR_WIDTH {
-                            let dst = &mut fake_fb[dst_offset + x];
-                            *dst = *color;
-                        }
-                        dst_offset += self.width;
-                    }
-                }
-
+                self.draw_cursor(cursor);
                 cursor += advance;
                 self.char_pos += 1;
             }
 
-            if has_cursor(&self.cursors, self.char_pos) {
-                // lifetime trick
-                let (pixels, color) = &mut self.render_data.as_mut().unwrap();
+            if let Some(x) = self.width.checked_sub(CURSOR_WIDTH) {
+                self.draw_cursor(x);
+            }
+        }
+    }
 
-                let fake_fb = pixels.as_rgba_mut();
-                if let Some(mut dst_offset) = self.width.checked_sub(CURSOR_WIDTH) {
-                    for _ in 0..self.font_size {
-                        for x in 0..CURSOR_WIDTH {
-                            let dst = &mut fake_fb[dst_offset + x];
-                            *dst = *color;
-                        }
-                        dst_offset += self.width;
-                    }
-                }
+    /// Draws the text cursor at `x` if there's one at the current character
+    fn draw_cursor(&mut self, x: usize) {
+        if has_cursor(&self.cursors, self.char_pos) {
+            let (pixels, color) = self.render_data.as_mut().unwrap();
+            let fake_fb = pixels.as_rgba_mut();
+            let mut dst_offset = x;
+            for _ in 0..self.font_size {
+                fake_fb[dst_offset..][..CURSOR_WIDTH].fill(*color);
+                dst_offset += self.width;
             }
         }
     }
@@ -343,7 +334,7 @@ impl<'a> GlyphRenderer<'a> {
     }
 
     /// Get the width of all processed glyphs.
-    pub fn width(self) -> usize {
+    pub fn width(&self) -> usize {
         self.width
     }
 
@@ -358,6 +349,28 @@ impl<'a> GlyphRenderer<'a> {
             panic!("StrTexture: No render color -> no texture");
         }
     }
+
+    /// Same as [`Self::texture`], but the glyphs are vertically
+    /// centered in a texture which is `height` pixels high.
+    ///
+    /// This allows rendering text in a node which is taller than the font size.
+    pub fn texture_with_height(self, height: usize) -> PixelSource {
+        if height <= self.font_size {
+            return self.texture();
+        }
+
+        if let Some((pixels, _color)) = self.render_data {
+            let line_len = self.width * 4;
+            let offset = ((height - self.font_size) / 2) * line_len;
+            let mut padded = vec![0; height * line_len];
+            padded[offset..][..pixels.len()].copy_from_slice(&pixels);
+
+            let pixel_buffer = RgbaPixelArray::new(padded.into_boxed_slice(), self.width, height);
+            PixelSource::TextureNoSSAA(Box::new(pixel_buffer))
+        } else {
+            panic!("StrTexture: No render color -> no texture");
+        }
+    }
 }
 
 /// Utility to compute the size of a whitespace based on font size.
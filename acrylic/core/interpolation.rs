@@ -0,0 +1,216 @@
+//! String interpolation in attribute values
+
+use crate::{ArcStr, String, Vec, Error, error, format};
+use lmfu::json::Value;
+
+/// Transformation of an interpolated value: `{root:path|upper}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Formatter {
+    /// Converts text to upper case
+    Upper,
+    /// Converts text to lower case
+    Lower,
+    /// Formats a number with a fixed number of decimals
+    Fixed(usize),
+    /// Formats a Unix timestamp (in seconds) as `YYYY-MM-DD`
+    Date,
+    /// Selects a word depending on a number: singular if it's 1, plural otherwise
+    Plural(ArcStr, ArcStr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(ArcStr),
+    Lookup {
+        namespace: ArcStr,
+        path: ArcStr,
+        formatters: Vec<Formatter>,
+    },
+}
+
+/// An attribute value made of text & JSON state lookups
+///
+/// Syntax: `Hello {root:user.name|upper}!`; braces are escaped by doubling them.
+/// Braces which don't enclose a `namespace:path` lookup are kept as is, so that
+/// `[0-9]{5}` is plain text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpolation {
+    pub segments: Vec<Segment>,
+}
+
+impl Interpolation {
+    pub fn parse(string: &str) -> Result<Self, Error> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = string;
+
+        while let Some(i) = rest.find(['{', '}']) {
+            text.push_str(&rest[..i]);
+            let brace = &rest[i..i + 1];
+            rest = &rest[i + 1..];
+
+            if let Some(after) = rest.strip_prefix(brace) {
+                text.push_str(brace);
+                rest = after;
+                continue;
+            }
+
+            let (lookup, after) = match rest.split_once('}') {
+                Some((lookup, after)) if brace == "{" && is_lookup(lookup) => (lookup, after),
+                _ => {
+                    text.push_str(brace);
+                    continue;
+                },
+            };
+
+            rest = after;
+
+            if !text.is_empty() {
+                segments.push(Segment::Text(core::mem::take(&mut text).into()));
+            }
+
+            segments.push(parse_lookup(lookup)?);
+        }
+
+        text.push_str(rest);
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text.into()));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Returns the unescaped text if there's no lookup
+    pub fn text(&self) -> Option<ArcStr> {
+        match self.segments.as_slice() {
+            [] => Some(ArcStr::new()),
+            [Segment::Text(text)] => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Checks that the content of braces starts with a namespace and a colon
+fn is_lookup(lookup: &str) -> bool {
+    let is_ns_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    match lookup.split_once(':') {
+        Some((namespace, _)) => {
+            let namespace = namespace.trim();
+            !namespace.is_empty() && namespace.chars().all(is_ns_char)
+        },
+        None => false,
+    }
+}
+
+fn parse_lookup(lookup: &str) -> Result<Segment, Error> {
+    let mut parts = lookup.split('|');
+    let target = parts.next().unwrap();
+
+    let (namespace, path) = target.split_once(':')
+        .ok_or_else(|| error!("Missing namespace in {{{}}}", lookup))?;
+
+    let formatters = parts.map(Formatter::parse).collect::<Result<_, _>>()?;
+
+    Ok(Segment::Lookup {
+        namespace: namespace.trim().into(),
+        path: path.trim().into(),
+        formatters,
+    })
+}
+
+impl Formatter {
+    /// Parses `name` or `name(arg, ...)`
+    pub fn parse(string: &str) -> Result<Self, Error> {
+        let string = string.trim();
+        let (name, args) = match string.strip_suffix(')').and_then(|s| s.split_once('(')) {
+            Some((name, args)) => (name.trim(), args.split(',').map(str::trim).collect()),
+            None => (string, Vec::new()),
+        };
+
+        match (name, args.as_slice()) {
+            ("upper", []) => Ok(Self::Upper),
+            ("lower", []) => Ok(Self::Lower),
+            ("date", []) => Ok(Self::Date),
+            ("fixed", [decimals]) => match decimals.parse() {
+                Ok(decimals) => Ok(Self::Fixed(decimals)),
+                Err(_) => Err(error!("Invalid number of decimals: {}", decimals)),
+            },
+            ("plural", [singular, plural]) => Ok(Self::Plural((*singular).into(), (*plural).into())),
+            _ => Err(error!("Invalid formatter: {}", string)),
+        }
+    }
+
+    /// Transforms a value, which is then formatted by the next formatter
+    pub fn apply(&self, value: Value) -> Result<Value, Error> {
+        let number = || match &value {
+            Value::Number(number) => Ok(*number),
+            Value::String(string) => string.trim().parse().map_err(|_| error!("Not a number: {}", string)),
+            _ => Err(error!("Not a number: {}", display(&value))),
+        };
+
+        let string = match self {
+            Self::Upper => display(&value).to_uppercase(),
+            Self::Lower => display(&value).to_lowercase(),
+            Self::Fixed(decimals) => format!("{:.*}", *decimals, number()?),
+            Self::Date => date(number()?),
+            Self::Plural(singular, plural) => match number()? == 1.0 {
+                true => String::from(&**singular),
+                false => String::from(&**plural),
+            },
+        };
+
+        Ok(Value::String(string.into()))
+    }
+}
+
+/// Textual representation of a scalar JSON value; `null` is displayed as nothing
+pub fn display(value: &Value) -> String {
+    match value {
+        Value::String(string) => String::from(&**string),
+        Value::Number(number) => format!("{}", number),
+        Value::Boolean(boolean) => format!("{}", boolean),
+        Value::Null => String::new(),
+        Value::Array(len) => format!("[{} items]", len),
+        Value::Object(keys) => format!("{{{} entries}}", keys.len()),
+    }
+}
+
+/// Converts a Unix timestamp to a `YYYY-MM-DD` date (proleptic Gregorian calendar, UTC)
+fn date(timestamp: f64) -> String {
+    let days = vek::num_traits::Float::floor(timestamp / 86400.0) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[test]
+fn interpolation() {
+    let parsed = Interpolation::parse("Hi {root:user.name|upper}, {{literal}}").unwrap();
+    assert_eq!(parsed.segments, [
+        Segment::Text("Hi ".into()),
+        Segment::Lookup {
+            namespace: "root".into(),
+            path: "user.name".into(),
+            formatters: [Formatter::Upper].into(),
+        },
+        Segment::Text(", {literal}".into()),
+    ]);
+
+    assert_eq!(Interpolation::parse("no {{lookup}}").unwrap().text().as_deref(), Some("no {lookup}"));
+    assert!(Interpolation::parse("{root:a|bold}").is_err());
+    assert_eq!(Interpolation::parse("{no-namespace}").unwrap().text().as_deref(), Some("{no-namespace}"));
+    assert_eq!(Interpolation::parse("[0-9]{2,5}").unwrap().text().as_deref(), Some("[0-9]{2,5}"));
+
+    let plural = Formatter::parse("plural(item, items)").unwrap();
+    assert_eq!(plural.apply(Value::Number(3.0)).unwrap(), Value::String("items".into()));
+    assert_eq!(Formatter::Fixed(2).apply(Value::Number(1.0 / 3.0)).unwrap(), Value::String("0.33".into()));
+    assert_eq!(Formatter::Date.apply(Value::Number(1709251200.0)).unwrap(), Value::String("2024-03-01".into()));
+}
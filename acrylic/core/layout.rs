@@ -2,10 +2,11 @@
 
 use super::visual::{
     Pixels, Ratio, Axis::{self, Horizontal, Vertical},
-    LayoutMode::*, Size, Position, SignedPixels,
+    LayoutMode::*, Overflow, Size, Position, SignedPixels,
 };
 use super::node::{Node, NodeTree, NodeKey};
 use super::app::Application;
+use super::transition;
 use super::for_each_child;
 use crate::Error;
 
@@ -37,32 +38,165 @@ pub fn hit_test(tree: &NodeTree, root: NodeKey, p: Position) -> NodeKey {
 pub fn get_scroll(app: &Application, container: NodeKey) -> (Axis, Option<SignedPixels>, Option<Pixels>) {
     let node = &app.view[container];
     let axis = node.config.get_length_axis();
-    let p = node.position.add_size(node.margin.top_left).get_for_axis(axis);
-    let content_capacity = node.size.get_for_axis(axis);
-    let mut scroll = None;
 
-    let mut cursor = Cursor::new(&app.view[container], node.position);
+    let mut cursor = Cursor::new(node, node.position);
     for_each_child!(app.view, container, child, {
-        if let None = scroll {
-            scroll = Some(p - app.view[child].position.get_for_axis(axis));
-        }
-
         cursor.advance(&app.view[child]);
     });
 
-    let content_length = cursor.finish();
+    let max_scroll = get_max_scroll(node, cursor.finish());
+    (axis, Some(node.scroll).filter(|v| !v.is_zero()), max_scroll)
+}
+
+fn get_max_scroll(container: &Node, content_length: Pixels) -> Option<Pixels> {
+    let content_capacity = container.size.get_for_axis(container.config.get_length_axis());
     let max_scroll = content_length.checked_sub(content_capacity);
-    (axis, scroll.filter(|v| !v.is_zero()), max_scroll.filter(|v| !v.is_zero()))
+    max_scroll.filter(|v| !v.is_zero())
 }
 
 /// Set the current scroll amount of a container
-pub fn scroll(app: &mut Application, container: NodeKey, axis: Axis, diff: SignedPixels) {
+///
+/// Its children are moved by `diff` pixels on `axis`; the
+/// scroll offset is kept in [`Node::scroll`] for the layout.
+/// The scroller of the container is then called.
+pub fn scroll(app: &mut Application, container: NodeKey, axis: Axis, diff: SignedPixels) -> Result<(), Error> {
+    app.view[container].scroll -= diff;
+    shift_children(app, container, axis, diff);
+    app.call_scroller(container)
+}
+
+fn shift_children(app: &mut Application, container: NodeKey, axis: Axis, diff: SignedPixels) {
     for_each_child!(app.view, container, child, {
-        scroll(app, child, axis, diff);
+        shift_children(app, child, axis, diff);
         app.view[child].position.add_to_axis(axis, diff);
     });
 }
 
+/// Scrolls a container by `diff` pixels, within its scrolling range
+///
+/// Like [`UserInputEvent::WheelY`], a positive `diff` moves the children
+/// forward. Returns `false` if the container couldn't scroll any further.
+///
+/// [`UserInputEvent::WheelY`]: super::event::UserInputEvent::WheelY
+pub fn scroll_by(app: &mut Application, container: NodeKey, diff: SignedPixels) -> Result<bool, Error> {
+    let (axis, current_scroll, max_scroll) = get_scroll(app, container);
+    let max_scroll = match max_scroll {
+        Some(max_scroll) => max_scroll.to_num::<SignedPixels>(),
+        None => return Ok(false),
+    };
+
+    let current_scroll = current_scroll.unwrap_or(SignedPixels::ZERO);
+    let new_scroll = (current_scroll - diff).clamp(SignedPixels::ZERO, max_scroll);
+
+    if new_scroll == current_scroll {
+        return Ok(false);
+    }
+
+    app.view[container].config.set_dirty(true);
+    scroll(app, container, axis, current_scroll - new_scroll)?;
+
+    Ok(true)
+}
+
+/// Thickness of scrollbars
+pub const SCROLLBAR_WIDTH: Pixels = Pixels::lit("6");
+
+const MIN_THUMB_LENGTH: Pixels = Pixels::lit("16");
+
+/// Geometry of the scrollbar of a container, see [`get_scrollbar`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Scrollbar {
+    pub axis: Axis,
+    /// Area in which the thumb moves
+    pub track: (Position, Size),
+    pub thumb: (Position, Size),
+    pub scroll: SignedPixels,
+    pub max_scroll: Pixels,
+}
+
+fn axis_rect(axis: Axis, start: SignedPixels, cross_start: SignedPixels, length: Pixels, thickness: Pixels) -> (Position, Size) {
+    match axis {
+        Horizontal => (Position::new(start, cross_start), Size::new(length, thickness)),
+        Vertical => (Position::new(cross_start, start), Size::new(thickness, length)),
+    }
+}
+
+/// Computes the scrollbar of a container
+///
+/// Returns `None` unless the container has [`Overflow::Scroll`]
+/// and its children don't fit in it.
+pub fn get_scrollbar(app: &Application, container: NodeKey) -> Option<Scrollbar> {
+    let node = &app.view[container];
+    if node.overflow != Overflow::Scroll {
+        return None;
+    }
+
+    let (axis, scroll, max_scroll) = get_scroll(app, container);
+    let scroll = scroll.unwrap_or(SignedPixels::ZERO);
+    let max_scroll = max_scroll?;
+    let cross = axis.complement();
+
+    // keep the scrollbar away from rounded corners
+    let capacity = node.size.get_for_axis(axis);
+    let track_length = capacity.checked_sub(node.margin.total_on(axis))?;
+    let track_start = node.position.get_for_axis(axis) + node.margin.top_left.get_for_axis(axis).to_num::<SignedPixels>();
+
+    let inset = node.margin.bottom_right.get_for_axis(cross) / 2;
+    let cross_end = node.position.add_size(node.size).get_for_axis(cross);
+    let cross_start = cross_end - (SCROLLBAR_WIDTH + inset).to_num::<SignedPixels>();
+
+    let (track_f, capacity_f) = (track_length.to_num::<f32>(), capacity.to_num::<f32>());
+    let visible = capacity_f / (capacity_f + max_scroll.to_num::<f32>());
+    let thumb_length = Pixels::from_num(track_f * visible).max(MIN_THUMB_LENGTH).min(track_length);
+
+    let progress = scroll.to_num::<f32>() / max_scroll.to_num::<f32>();
+    let thumb_offset = (track_length - thumb_length).to_num::<f32>() * progress;
+    let thumb_start = track_start + SignedPixels::from_num(thumb_offset);
+
+    Some(Scrollbar {
+        axis,
+        track: axis_rect(axis, track_start, cross_start, track_length, SCROLLBAR_WIDTH),
+        thumb: axis_rect(axis, thumb_start, cross_start, thumb_length, SCROLLBAR_WIDTH),
+        scroll,
+        max_scroll,
+    })
+}
+
+/// See [`Application::scroll_into_view`]
+pub(crate) fn scroll_into_view(app: &mut Application, node_key: NodeKey) -> Result<(), Error> {
+    let mut current = node_key;
+    while let Some(parent) = app.view.parent(current) {
+        let container = &app.view[parent];
+        if container.overflow == Overflow::Scroll {
+            let axis = container.config.get_length_axis();
+            let view_start = container.position.add_size(container.margin.top_left).get_for_axis(axis);
+            let view_end = container.position.add_size(container.size).get_for_axis(axis)
+                - container.margin.bottom_right.get_for_axis(axis).to_num::<SignedPixels>();
+
+            let node = &app.view[node_key];
+            let start = node.position.get_for_axis(axis);
+            let end = node.position.add_size(node.size).get_for_axis(axis);
+
+            // the start of the node wins if it's larger than the container
+            let diff = if start < view_start {
+                view_start - start
+            } else if end > view_end {
+                (view_end - end).max(view_start - start)
+            } else {
+                SignedPixels::ZERO
+            };
+
+            if diff != SignedPixels::ZERO {
+                scroll_by(app, parent, diff)?;
+            }
+        }
+
+        current = parent;
+    }
+
+    Ok(())
+}
+
 /// (Re)Compute the layout of a view
 pub fn compute_layout(app: &mut Application, root: NodeKey) -> Result<(), Error> {
     app.view[root].config.set_size_found(true);
@@ -75,11 +209,71 @@ pub fn compute_layout(app: &mut Application, root: NodeKey) -> Result<(), Error>
     compute_positions(app, root, Position::default())
 }
 
+/// Compute the layout of an overlay
+///
+/// Wrapping and fixed-length contents get their natural size;
+/// other contents fill the framebuffer. The content is placed
+/// at `anchor` (or centered if it's `None`), then moved so that
+/// it fits in the framebuffer.
+///
+/// Returns the rectangle of the content.
+pub fn compute_overlay_layout(
+    app: &mut Application,
+    holder: NodeKey,
+    anchor: Option<Position>,
+    fb_size: Size,
+) -> Result<(Position, Size), Error> {
+    let content = match app.view.first_child(holder) {
+        Some(content) => content,
+        None => return Ok((Position::zero(), Size::zero())),
+    };
+
+    // the holder is a container on the other axis,
+    // so that the content can wrap its children
+    let axis = app.view[content].config.get_content_axis().complement();
+    app.view[holder].config.set_content_axis(axis);
+    app.view[holder].config.set_size_found(true);
+    app.view[holder].size = fb_size;
+
+    let tree = &mut app.view;
+    let natural = match tree[content].config.get_layout_mode() {
+        WrapContent => compute_wrapper_size(tree, axis, content, None),
+        Fixed(l) => compute_fixed_size(tree, axis, content, None, l),
+        _ => None,
+    };
+
+    if natural.is_none() {
+        let cross = fb_size.get_for_axis(axis.complement());
+        compute_children_sizes(tree, holder, cross);
+        compute_remaining_children_sizes(tree, holder, cross);
+    }
+
+    let size = tree[content].size;
+    let max = Position::zero().add_size(Size::new(
+        fb_size.w.checked_sub(size.w).unwrap_or(Pixels::ZERO),
+        fb_size.h.checked_sub(size.h).unwrap_or(Pixels::ZERO),
+    ));
+
+    let anchor = anchor.unwrap_or(Position::new(max.x / 2, max.y / 2));
+    let position = Position::new(
+        anchor.x.clamp(SignedPixels::ZERO, max.x),
+        anchor.y.clamp(SignedPixels::ZERO, max.y),
+    );
+
+    compute_positions(app, holder, position)?;
+
+    Ok((position, size))
+}
+
 impl Node {
     #[inline(always)]
     fn set_size(&mut self, size: Size) {
         self.config.set_size_found(true);
         if self.size != size {
+            if let Some(transition) = &mut self.transition {
+                transition.resized_from.get_or_insert(self.size);
+            }
+
             self.config.set_resized(true);
             self.size = size;
         }
@@ -87,7 +281,13 @@ impl Node {
 }
 
 fn compute_positions(app: &mut Application, key: NodeKey, top_left: Position) -> Result<(), Error> {
-    let mut cursor = Cursor::new(&app.view[key], top_left);
+    let axis = app.view[key].config.get_length_axis();
+    let offset = app.view[key].scroll;
+
+    let mut children_top_left = top_left;
+    children_top_left.add_to_axis(axis, -offset);
+
+    let mut cursor = Cursor::new(&app.view[key], children_top_left);
     for_each_child!(app.view, key, child, {
         let size_found = app.view[child].config.get_size_found();
         if size_found {
@@ -100,11 +300,16 @@ fn compute_positions(app: &mut Application, key: NodeKey, top_left: Position) ->
         }
 
         let position = cursor.advance(&app.view[child]);
-        let moved = app.view[child].position != position;
+        let old_position = app.view[child].position;
+        let moved = old_position != position;
         app.view[child].position = position;
 
         compute_positions(app, child, position)?;
 
+        if app.view[child].transition.is_some() {
+            transition::relocated(app, child, old_position)?;
+        }
+
         let resized = app.view[child].config.get_resized();
         if moved | resized {
             app.view[child].config.set_dirty(true);
@@ -116,7 +321,18 @@ fn compute_positions(app: &mut Application, key: NodeKey, top_left: Position) ->
         }
     });
 
-    Ok(())
+    // the content might have shrunk
+    if !offset.is_zero() {
+        let max_scroll = get_max_scroll(&app.view[key], cursor.finish());
+        let max_scroll = max_scroll.map(|v| v.to_num()).unwrap_or(SignedPixels::ZERO);
+
+        if offset > max_scroll {
+            app.view[key].config.set_dirty(true);
+            scroll(app, key, axis, offset - max_scroll)?;
+        }
+    }
+
+    app.call_scroller(key)
 }
 
 fn handle_children(tree: &mut NodeTree, container: NodeKey) {
@@ -134,7 +350,7 @@ fn get_children_length_on_cont_axis(tree: &mut NodeTree, container: NodeKey) ->
     let mut length = Pixels::ZERO;
 
     for_each_child!(tree, container, child, {
-        if tree[child].config.get_size_found() {
+        if tree[child].config.get_size_found() && !is_hidden(&tree[child]) {
             length += tree[child].size.get_for_axis(axis) + gap;
         }
     });
@@ -205,7 +421,7 @@ fn compute_remaining_children_sizes(tree: &mut NodeTree, container: NodeKey, cro
             if let Remaining(q) = tree[child].config.get_layout_mode() {
                 quota_sum += q;
                 used += gap;
-            } else {
+            } else if !is_hidden(&tree[child]) {
                 used += tree[child].size.get_for_axis(axis) + gap;
             }
         });
@@ -343,6 +559,10 @@ fn compute_chunks_size(
     let mut chunk_length = Pixels::ZERO;
 
     for_each_child!(tree, this, child, {
+        if is_hidden(&tree[child]) {
+            continue;
+        }
+
         let child_length = tree[child].size.get_for_axis(this_axis);
         let new_chunk_length = chunk_length + gap + child_length;
         if new_chunk_length > cross {
@@ -490,6 +710,11 @@ impl Cursor {
     }
 
     pub fn advance(&mut self, child: &Node) -> Position {
+        // hidden children take no space, and no gap
+        if is_hidden(child) {
+            return self.top_left;
+        }
+
         let child_length = child.size.get_for_axis(self.axis);
         let with_gap = child_length + self.gap;
 
@@ -524,8 +749,42 @@ impl Cursor {
     }
 }
 
+/// Nodes which are left out of the layout (e.g. hidden conditional branches)
+fn is_hidden(node: &Node) -> bool {
+    node.config.get_layout_mode() == Unset
+}
+
 fn adjust_cross(cont: &Node, cross: Pixels) -> Option<Pixels> {
     let axis = cont.config.get_content_axis();
     let to_sub = cont.margin.total_on(axis.complement());
     cross.checked_sub(to_sub)
 }
+
+#[test]
+fn hidden_children_take_no_gap() {
+    let mut tree = NodeTree::new();
+    let container = tree.create();
+    tree[container].config.set_content_axis(Horizontal);
+    tree[container].config.set_content_gap(Pixels::from_num(10));
+    tree[container].config.set_layout_mode(WrapContent);
+
+    let mut children = [None; 3];
+    for (i, width) in [Some(20), None, Some(30)].into_iter().enumerate() {
+        let child = tree.create();
+        tree.append_children(child, container);
+        if let Some(width) = width {
+            let width = Pixels::from_num(width);
+            tree[child].config.set_layout_mode(Fixed(width));
+            tree[child].size = Size::new(width, Pixels::from_num(10));
+        }
+        tree[child].config.set_size_found(true);
+        children[i] = Some(child);
+    }
+
+    assert_eq!(get_children_length_on_cont_axis(&mut tree, container), Pixels::from_num(60));
+
+    let mut cursor = Cursor::new(&tree[container], Position::zero());
+    let positions = children.map(|child| cursor.advance(&tree[child.unwrap()]).x);
+    assert_eq!(positions[2], SignedPixels::from_num(30));
+    assert_eq!(cursor.finish(), Pixels::from_num(60));
+}
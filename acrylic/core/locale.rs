@@ -0,0 +1,158 @@
+//! Translation tables & plural rules
+
+use crate::{ArcStr, Vec, Error, error, ro_string};
+use lmfu::json::{JsonFile, Value, Path};
+
+/// Translation tables of the application, see [`Application::set_locale`]
+///
+/// A table is a JSON object, keyed by message ID; messages can be
+/// grouped in nested objects (`inbox.title` is the `title` message
+/// of the `inbox` object). Messages which depend on a number are
+/// objects keyed by plural category: `zero`, `one`, `two`, `few`,
+/// `many` & `other`, which is required.
+///
+/// [`Application::set_locale`]: super::app::Application::set_locale
+pub struct Localization {
+    pub(crate) locale: ArcStr,
+    pub(crate) fallback: Option<ArcStr>,
+    tables: Vec<(ArcStr, JsonFile)>,
+}
+
+impl Localization {
+    pub fn new() -> Self {
+        Self {
+            locale: ro_string!("en"),
+            fallback: None,
+            tables: Vec::new(),
+        }
+    }
+
+    /// Adds a translation table; messages of an existing table for the same locale are replaced
+    pub fn add_table(&mut self, locale: &str, json: &str) -> Result<(), Error> {
+        let table = JsonFile::new(Some(json)).map_err(|e| error!("Invalid translation table for {}: {:?}", locale, e))?;
+        self.tables.retain(|(l, _)| &**l != locale);
+        self.tables.push((locale.into(), table));
+        Ok(())
+    }
+
+    /// Locales in which messages are searched: `fr-CA`, `fr`, then the fallback locale
+    pub fn chain(&self) -> Vec<&str> {
+        let mut chain = Vec::from([&*self.locale]);
+
+        if let Some((language, _)) = self.locale.split_once(['-', '_']) {
+            chain.push(language);
+        }
+
+        if let Some(fallback) = self.fallback.as_deref() {
+            if !chain.contains(&fallback) {
+                chain.push(fallback);
+            }
+        }
+
+        chain
+    }
+
+    /// Finds a message in the first locale of [`Self::chain`] which has it
+    ///
+    /// Also returns the locale of the message, for plural rules.
+    pub fn lookup(&self, path: &Path) -> Option<(&str, &Value)> {
+        for locale in self.chain() {
+            let table = self.tables.iter().find(|(l, _)| &**l == locale);
+            if let Some((locale, table)) = table {
+                match &table[path] {
+                    Value::Null => continue,
+                    value => return Some((locale, value)),
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Finds a message; plural messages need a number to select their form
+    pub fn message(&self, path: &Path, count: Option<f64>) -> Option<ArcStr> {
+        let (locale, value) = self.lookup(path)?;
+
+        match (value, count) {
+            (Value::String(message), _) => Some(message.clone()),
+            (Value::Object(_), Some(n)) => {
+                let (_, table) = self.tables.iter().find(|(l, _)| &**l == locale)?;
+                let form = path.clone().index_str(plural_category(locale, n)).clone();
+                let other = path.clone().index_str("other").clone();
+
+                match (&table[&form], &table[&other]) {
+                    (Value::String(message), _) | (_, Value::String(message)) => Some(message.clone()),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plural category of a number in some locale
+///
+/// This covers the (simplified) CLDR rules of common languages;
+/// others use the English rule.
+pub fn plural_category(locale: &str, n: f64) -> &'static str {
+    let language = locale.split(['-', '_']).next().unwrap();
+    let integer = vek::num_traits::Float::fract(n) == 0.0 && n >= 0.0;
+    let (i10, i100) = ((n % 10.0) as u64, (n % 100.0) as u64);
+
+    match language {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" => "other",
+        "fr" | "pt" => match (0.0..2.0).contains(&n) {
+            true => "one",
+            false => "other",
+        },
+        "ru" | "uk" | "be" | "pl" if integer => match (i10, i100) {
+            (1, 11) => "many",
+            (1, _) if language != "pl" || n == 1.0 => "one",
+            (2..=4, 12..=14) => "many",
+            (2..=4, _) => "few",
+            _ => "many",
+        },
+        "ru" | "uk" | "be" | "pl" => "other",
+        _ => match n == 1.0 {
+            true => "one",
+            false => "other",
+        },
+    }
+}
+
+#[test]
+fn translations() {
+    let mut localization = Localization::new();
+    localization.add_table("en", r#"{ "inbox": { "title": "Inbox", "empty": "Nothing here" } }"#).unwrap();
+    localization.add_table("fr", r#"{ "inbox": { "title": "Boîte de réception" } }"#).unwrap();
+    localization.locale = "fr-CA".into();
+    localization.fallback = Some("en".into());
+
+    assert_eq!(localization.chain(), ["fr-CA", "fr", "en"]);
+
+    let title = Path::from(["inbox", "title"]);
+    let empty = Path::from(["inbox", "empty"]);
+    assert_eq!(localization.lookup(&title), Some(("fr", &Value::String("Boîte de réception".into()))));
+    assert_eq!(localization.lookup(&empty), Some(("en", &Value::String("Nothing here".into()))));
+    assert_eq!(localization.lookup(&Path::from(["missing"])), None);
+
+    localization.add_table("en", r#"{ "unread": { "one": "1 message", "other": "{root:n} messages" } }"#).unwrap();
+    localization.locale = "de".into();
+    let unread = Path::from(["unread"]);
+    assert_eq!(localization.message(&unread, Some(1.0)).as_deref(), Some("1 message"));
+    assert_eq!(localization.message(&unread, Some(3.0)).as_deref(), Some("{root:n} messages"));
+    assert_eq!(localization.message(&unread, None), None);
+
+    assert_eq!(plural_category("en", 1.0), "one");
+    assert_eq!(plural_category("fr", 0.0), "one");
+    assert_eq!(plural_category("ru", 22.0), "few");
+    assert_eq!(plural_category("ru", 11.0), "many");
+    assert_eq!(plural_category("pl", 21.0), "many");
+    assert_eq!(plural_category("ja", 1.0), "other");
+}
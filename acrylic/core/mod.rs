@@ -1,11 +1,16 @@
 //! Events, Rendering, Layout, JSON State, ...
 
 pub mod app;
+pub mod clock;
 pub mod event;
 pub mod glyph;
+pub mod interpolation;
 pub mod text_edit;
+pub mod transition;
 pub mod layout;
+pub mod locale;
 pub mod node;
+pub mod overlay;
 pub mod state;
 pub mod style;
 pub mod visual;
@@ -1,7 +1,8 @@
 //! The Node structure
 
 use super::xml::{OptionalXmlNodeIndex, XmlTagParameters};
-use super::visual::{PixelSource, NodeConfig, Margin, Size, Position};
+use super::visual::{PixelSource, NodeConfig, Margin, Size, Position, SignedPixels, Overflow};
+use super::transition::Transition;
 use oakwood::{Cookie64, tree, index};
 use super::event::Handlers;
 use crate::{ArcStr, Box};
@@ -27,17 +28,21 @@ pub struct Node {                                 // bits    div4
     pub size: Size,                               // 2x4     2
     pub position: Position,                       // 2x4     2
 
-    pub background: PixelSource,                  // 2x8     4
-    pub foreground: PixelSource,                  // 2x8     4
+    pub background: PixelSource,                  // 3x8     6
+    pub foreground: PixelSource,                  // 3x8     6
 
     pub factory: OptionalMutatorIndex,            // 1x2
     pub style_override: OptionalStyleIndex,       // 1x2     1
 
     pub xml_node_index: OptionalXmlNodeIndex,     // 1x4     1
 
-    // todo:
-    // pub transition: Transition,
-}                                                 //         21x4
+    pub transition: Option<Box<Transition>>,      // 1x8     2
+
+    /// Scroll offset of the children, on the length axis
+    pub scroll: SignedPixels,                     // 1x4     1
+
+    pub overflow: Overflow,                       // 1x1     1 (padded)
+}                                                 //         28x4
 
 /// XML Tags & other event handlers are defined as Mutators
 pub struct Mutator {
@@ -48,6 +53,17 @@ pub struct Mutator {
     pub storage: Option<Box<dyn Any>>,
 }
 
+/// Mutators of a third-party crate
+///
+/// In XML layouts, the tags of a library are prefixed, so that libraries
+/// cannot collide with each other or with built-in tags: `<charts:line>`.
+/// See [`Application::add_library`](super::app::Application::add_library).
+pub struct MutatorLibrary {
+    /// Prefix of the tags of this library
+    pub prefix: ArcStr,
+    pub mutators: &'static [Mutator],
+}
+
 /// Utility function for event handlers to get and downcast their storage
 pub fn get_storage<T: Any>(mutators: &mut [Mutator], m: MutatorIndex) -> Option<&mut T> {
     mutators[usize::from(m)].storage.as_mut()?.downcast_mut()
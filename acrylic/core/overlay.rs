@@ -0,0 +1,52 @@
+//! Layers above the main view: popups, dropdowns, dialogs
+
+use super::event::DEFAULT_HANDLERS;
+use super::node::{NodeKey, Mutator};
+use super::xml::XmlNodeIndex;
+use super::visual::Position;
+use crate::{Vec, ro_string};
+
+#[cfg(doc)]
+use super::app::Application;
+
+/// Where the content of an overlay is placed
+///
+/// In all cases, the content is then moved so that it fits in the framebuffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Anchor {
+    /// Right below the owner of the overlay, aligned on its left edge
+    Below,
+    /// At a fixed position, for instance the focus coordinates
+    At(Position),
+    /// At the center of the framebuffer
+    Center,
+}
+
+/// A layer displayed above the main view
+///
+/// Overlays are stacked: the last one is painted last and hit-tested first.
+/// Each one has its own root node (the holder), which isn't attached to the
+/// main view. See [`Application::push_overlay`].
+pub struct Overlay {
+    /// Root node of the overlay; its only child is the content
+    pub holder: NodeKey,
+    /// The node which opened the overlay
+    ///
+    /// JSON state namespaces and styles are inherited from it.
+    pub owner: NodeKey,
+    /// XML node from which the content is (re-)created
+    pub xml_node_index: XmlNodeIndex,
+    pub anchor: Anchor,
+    /// Modal overlays cover the whole framebuffer; nodes under
+    /// them cannot be hovered, clicked or focused.
+    pub modal: bool,
+    pub(crate) owner_path: (NodeKey, Vec<usize>),
+}
+
+/// Factory of overlay holders; it has no XML tag.
+pub const OVERLAY_MUTATOR: Mutator = Mutator {
+    name: ro_string!("OverlayMutator"),
+    xml_params: None,
+    handlers: DEFAULT_HANDLERS,
+    storage: None,
+};
@@ -1,15 +1,21 @@
 //! JSON State
 
 use super::{app::Application, node::NodeKey};
-use crate::{ArcStr, ro_string, Error};
-use lmfu::json::Path;
+use crate::{ArcStr, ro_string, Error, error, Vec};
+use lmfu::json::{JsonFile, Value, Path, parse_path};
+use core::cmp::Ordering;
 
+/// Appends the path of the current item of a namespace to `path`
+///
+/// `ns_creator` is the node which created the namespace, and
+/// `ns_user` is the node which looks a value up. Namespaces which
+/// iterate over an array or an object return their current item.
 pub type NamespaceCallback = fn(
     app: &Application,
     ns_creator: NodeKey,
     ns_user: NodeKey,
     path: &mut Path,
-) -> Result<(), Error>;
+) -> Result<Option<Item>, Error>;
 
 #[derive(Clone)]
 pub struct Namespace {
@@ -18,8 +24,19 @@ pub struct Namespace {
     pub callback: NamespaceCallback,
 }
 
-fn root_ns_callback(_: &Application, _: NodeKey, _: NodeKey, _: &mut Path) -> Result<(), Error> {
-    Ok(())
+/// Current item of an iterating namespace
+///
+/// Its properties can be used in attributes: `item:@index` & `item:@key`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    /// Position of the item in its array or object
+    pub index: usize,
+    /// Key of the item, when iterating over an object
+    pub key: Option<ArcStr>,
+}
+
+fn root_ns_callback(_: &Application, _: NodeKey, _: NodeKey, _: &mut Path) -> Result<Option<Item>, Error> {
+    Ok(None)
 }
 
 pub fn root_ns() -> Namespace {
@@ -29,3 +46,192 @@ pub fn root_ns() -> Namespace {
         callback: root_ns_callback,
     }
 }
+
+/// Namespace of translated messages: `tr:text="inbox.title"`
+///
+/// Its paths point into the translation tables of the app instead
+/// of its JSON state; see [`Application::set_locale`].
+pub fn tr_ns() -> Namespace {
+    Namespace {
+        name: ro_string!("tr"),
+        path: Path::new(),
+        callback: root_ns_callback,
+    }
+}
+
+/// Appends the key of an object entry or the index of an array item to `path`
+pub fn select_item(state: &JsonFile, path: &mut Path, index: usize) -> Result<Item, Error> {
+    let key = match &state[&*path] {
+        Value::Object(keys) => match keys.get(index) {
+            Some(key) => Some(ArcStr::from(&**key)),
+            None => return Err(error!("Object has no entry #{}", index)),
+        },
+        _ => None,
+    };
+
+    match &key {
+        Some(key) => path.index_str(key),
+        None => path.index_num(index),
+    };
+
+    Ok(Item { index, key })
+}
+
+/// Condition of the `where` attribute of iterating containers
+///
+/// Syntax: `path` (the value is truthy), `!path` (it isn't),
+/// `path == literal` or `path != literal`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter<'a> {
+    pub path: &'a str,
+    pub expected: Option<&'a str>,
+    pub negate: bool,
+}
+
+impl<'a> Filter<'a> {
+    pub fn parse(string: &'a str) -> Self {
+        let (path, expected, negate) = if let Some((path, literal)) = string.split_once("!=") {
+            (path, Some(literal.trim()), true)
+        } else if let Some((path, literal)) = string.split_once("==") {
+            (path, Some(literal.trim()), false)
+        } else if let Some(path) = string.trim().strip_prefix('!') {
+            (path, None, true)
+        } else {
+            (string, None, false)
+        };
+
+        Self { path: path.trim(), expected, negate }
+    }
+
+    pub fn matches(&self, value: &Value) -> bool {
+        let result = match self.expected {
+            Some(literal) => equals(value, literal),
+            None => truthy(value),
+        };
+
+        result != self.negate
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Array(len) => *len > 0,
+        Value::Object(keys) => !keys.is_empty(),
+        Value::String(string) => !string.is_empty(),
+        Value::Number(number) => *number != 0.0,
+        Value::Boolean(boolean) => *boolean,
+        Value::Null => false,
+    }
+}
+
+fn equals(value: &Value, literal: &str) -> bool {
+    // quotes are optional around strings
+    let unquoted = literal.strip_prefix('"').and_then(|l| l.strip_suffix('"'));
+
+    match value {
+        Value::String(string) => &**string == unquoted.unwrap_or(literal),
+        Value::Number(number) => literal.parse::<f64>() == Ok(*number),
+        Value::Boolean(boolean) => literal.parse::<bool>() == Ok(*boolean),
+        Value::Null => literal == "null",
+        _ => false,
+    }
+}
+
+/// Orders JSON values of different types: null, booleans, numbers, strings, others
+fn compare(a: &Value, b: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Boolean(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        _ => 4,
+    };
+
+    match (a, b) {
+        (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Positions of the items of an array or object which match a
+/// [`Filter`], in the order of the values at `sort_by`
+///
+/// Items are sorted in descending order if `sort_by` starts with `-`;
+/// items with equal values keep their original order.
+pub fn select_items(
+    state: &JsonFile,
+    base: &Path,
+    len: usize,
+    filter: Option<&str>,
+    sort_by: Option<&str>,
+) -> Result<Vec<usize>, Error> {
+    let item_value = |index: usize, relative: &str| -> Result<&Value, Error> {
+        let mut path = base.clone();
+        select_item(state, &mut path, index)?;
+        path.append(parse_path(relative));
+        Ok(&state[&path])
+    };
+
+    let filter = filter.map(Filter::parse);
+    let mut indices = Vec::with_capacity(len);
+    for index in 0..len {
+        let selected = match &filter {
+            Some(filter) => filter.matches(item_value(index, filter.path)?),
+            None => true,
+        };
+
+        if selected {
+            indices.push(index);
+        }
+    }
+
+    if let Some(sort_by) = sort_by {
+        let (sort_by, descending) = match sort_by.strip_prefix('-') {
+            Some(sort_by) => (sort_by, true),
+            None => (sort_by, false),
+        };
+
+        let mut keyed = Vec::with_capacity(indices.len());
+        for index in indices {
+            keyed.push((item_value(index, sort_by)?, index));
+        }
+
+        keyed.sort_by(|(a, _), (b, _)| match descending {
+            true => compare(b, a),
+            false => compare(a, b),
+        });
+
+        indices = keyed.into_iter().map(|(_, index)| index).collect();
+    }
+
+    Ok(indices)
+}
+
+#[test]
+fn select() {
+    let json = r#"{ "messages": [
+        { "read": true, "date": 3 },
+        { "date": 1 },
+        { "date": 2 }
+    ] }"#;
+
+    let state = JsonFile::new(Some(json)).unwrap();
+    let base = Path::from(["messages"]);
+
+    let unread = select_items(&state, &base, 3, Some("!read"), Some("-date")).unwrap();
+    assert_eq!(unread, [2, 1]);
+
+    let first = select_items(&state, &base, 3, Some("date == 3"), None).unwrap();
+    assert_eq!(first, [0]);
+
+    let all = select_items(&state, &base, 3, None, Some("date")).unwrap();
+    assert_eq!(all, [1, 2, 0]);
+
+    assert_eq!(Filter::parse("author != \"me\""), Filter {
+        path: "author",
+        expected: Some("\"me\""),
+        negate: true,
+    });
+}
@@ -36,6 +36,17 @@ pub struct Style {
     pub outline: RGBA8,
 }
 
+impl Style {
+    /// The foreground color, blended halfway into the background color
+    ///
+    /// This is used for placeholders and other secondary content.
+    pub fn dimmed_foreground(&self) -> RGBA8 {
+        let mix = |fg: u8, bg: u8| ((fg as u16 + bg as u16) / 2) as u8;
+        let (fg, bg) = (self.foreground, self.background);
+        RGBA8::new(mix(fg.r, bg.r), mix(fg.g, bg.g), mix(fg.b, bg.b), fg.a)
+    }
+}
+
 pub const DEFAULT_STYLE: &'static str = "default";
 
 /// A theme which can be used by the app.
@@ -2,13 +2,21 @@
 //!
 //! The handler is pub(crate); it's not shown in the documentation.
 
-use crate::core::visual::{Pixels, SignedPixels};
+use crate::core::visual::{Pixels, SignedPixels, PixelSource};
 use crate::core::event::UserInputEvent;
-use crate::{Error, error, String, ArcStr};
+use crate::core::style::Style;
+use crate::{Error, error, String, ArcStr, Box, Rc, HashMap, format};
 use crate::core::app::Application;
 use crate::core::glyph::get_font;
 use crate::core::for_each_child;
-use crate::core::node::NodeKey;
+use crate::core::node::{NodeKey, MutatorIndex, get_storage};
+
+use lmfu::json::{Value, Path};
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+use regex_automata::{dfa::{dense, Automaton, StartKind}, Input, Anchored, MatchKind};
+
+/// U+00AD, an invisible line break opportunity
+pub const SOFT_HYPHEN: char = '\u{AD}';
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Cursor {
@@ -16,54 +24,419 @@ pub struct Cursor {
     pub char_pos: usize,
 }
 
-/// Splits a string at each whitespace character
-pub fn break_ws(text: &str) -> impl Iterator<Item=&str> {
-    text.split(char::is_whitespace)
+/// Attribute indices of an editable textual node
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct TextAttributes {
+    pub editable: usize,
+    pub font: usize,
+    pub text: usize,
+    /// Optional text displayed while the node is empty
+    pub placeholder: usize,
+    /// Optional masking character (password fields)
+    pub mask: Option<usize>,
+    /// See [`InputType`]
+    pub input: usize,
+    /// Optional regular expression which the text must match
+    pub pattern: usize,
 }
 
-fn get_cursor(
-    text_cursors: &[Cursor],
-    paragraph: bool,
+/// What an editable textual node accepts
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InputType {
+    /// Any text; it is stored as a JSON string
+    Text,
+    /// Signed integers; they are stored as JSON numbers
+    Integer,
+    /// Signed decimal numbers; they are stored as JSON numbers
+    Number,
+}
+
+impl InputType {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        match input {
+            "text" => Ok(Self::Text),
+            "integer" => Ok(Self::Integer),
+            "number" => Ok(Self::Number),
+            other => Err(error!("Invalid input type: {:?} (expected text, integer or number)", other)),
+        }
+    }
+
+    fn pattern(self) -> Option<&'static str> {
+        match self {
+            Self::Text => None,
+            Self::Integer => Some(r"-?[0-9]+"),
+            Self::Number => Some(r"-?[0-9]+(\.[0-9]+)?([eE][-+]?[0-9]+)?"),
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        self != Self::Text
+    }
+}
+
+/// Result of the validation of a text
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Validity {
+    /// The text is a valid value
+    Valid,
+    /// The text isn't valid, but it's the start of a valid value
+    Incomplete,
+    /// No valid value starts with this text
+    Invalid,
+}
+
+/// A compiled `pattern`
+pub type Pattern = dense::DFA<crate::Vec<u32>>;
+
+/// Compiled patterns of a textual mutator, keyed by pattern
+type PatternCache = HashMap<str, Rc<Pattern>>;
+
+/// Compiles a `pattern`, which must match whole texts
+pub fn compile_pattern(pattern: &str) -> Result<Pattern, Error> {
+    // MatchKind::All: dead states really mean "no valid value starts with this"
+    let config = dense::Config::new().match_kind(MatchKind::All).start_kind(StartKind::Anchored);
+    dense::Builder::new().configure(config).build(pattern)
+        .map_err(|e| error!("Invalid pattern {:?}: {}", pattern, e))
+}
+
+/// Validates a text against an input type and an optional compiled pattern
+///
+/// If present, `pattern` replaces the built-in pattern of the input type.
+/// Empty texts are always valid.
+pub fn validate(text: &str, input_type: InputType, pattern: Option<&Pattern>) -> Result<Validity, Error> {
+    let dfa = match pattern {
+        Some(dfa) => dfa,
+        None => return Ok(Validity::Valid),
+    };
+
+    if text.is_empty() {
+        return Ok(Validity::Valid);
+    }
+
+    let input = Input::new(text).anchored(Anchored::Yes);
+    let mut state = dfa.start_state_forward(&input)
+        .map_err(|e| error!("Pattern error: {}", e))?;
+
+    for byte in text.bytes() {
+        state = dfa.next_state(state, byte);
+        if dfa.is_dead_state(state) {
+            return Ok(Validity::Invalid);
+        }
+    }
+
+    // matches are delayed by one byte, so the last state might be a
+    // match state for a shorter text: check that it can lead somewhere.
+    let eoi_state = dfa.next_eoi_state(state);
+    let parses = !input_type.is_numeric() || text.parse::<f64>().is_ok();
+    let can_grow = (0..=u8::MAX).any(|byte| !dfa.is_dead_state(dfa.next_state(state, byte)));
+
+    Ok(match (dfa.is_match_state(eoi_state) && parses, can_grow) {
+        (true, _) => Validity::Valid,
+        (false, true) => Validity::Incomplete,
+        (false, false) => Validity::Invalid,
+    })
+}
+
+#[test]
+fn validation() {
+    use Validity::*;
+    let compile = |input_type: InputType| compile_pattern(input_type.pattern().unwrap()).unwrap();
+    let (number_dfa, integer_dfa) = (compile(InputType::Number), compile(InputType::Integer));
+    let number = |text| validate(text, InputType::Number, Some(&number_dfa)).unwrap();
+    let integer = |text| validate(text, InputType::Integer, Some(&integer_dfa)).unwrap();
+
+    assert_eq!(integer("-12"), Valid);
+    assert_eq!(integer("-"), Incomplete);
+    assert_eq!(integer("1.5"), Invalid);
+    assert_eq!(number("1.5e-3"), Valid);
+    assert_eq!(number("1.5e"), Incomplete);
+    assert_eq!(number("1a"), Invalid);
+    assert_eq!(number("12"), Valid);
+
+    let dfa = compile_pattern("[a-z]+@[a-z]+").unwrap();
+    let pattern = Some(&dfa);
+    assert_eq!(validate("me@", InputType::Text, pattern).unwrap(), Incomplete);
+    assert_eq!(validate("me@home", InputType::Text, pattern).unwrap(), Valid);
+    assert_eq!(validate("@", InputType::Text, pattern).unwrap(), Invalid);
+}
+
+/// Reads the input type & pattern of a node, then validates `text`
+pub(crate) fn validity(
+    app: &mut Application,
+    node_key: NodeKey,
+    attributes: TextAttributes,
     text: &str,
-) -> Result<(Cursor, usize), Error> {
-    let cursor = match text_cursors.get(0) {
-        Some(cursor) => Ok(*cursor),
-        None => Err(error!("TextInsert but no cursor?")),
-    }?;
+) -> Result<(InputType, Validity), Error> {
+    let input: ArcStr = app.attr(node_key, attributes.input)?;
+    let pattern: Option<ArcStr> = app.attr(node_key, attributes.pattern)?;
+    let input_type = InputType::parse(&input)?;
+
+    let dfa = match pattern.as_deref().or(input_type.pattern()) {
+        Some(pattern) => Some(cached_pattern(app, node_key, pattern)?),
+        None => None,
+    };
+
+    Ok((input_type, validate(text, input_type, dfa.as_deref())?))
+}
+
+/// Initializer of textual mutators: sets up their cache of compiled patterns
+pub(crate) fn initializer(app: &mut Application, m: MutatorIndex) -> Result<(), Error> {
+    let mutator = &mut app.mutators[usize::from(m)];
+    assert!(mutator.storage.is_none());
+    mutator.storage = Some(Box::new(PatternCache::new()));
+    Ok(())
+}
+
+/// Compiles a pattern once per textual mutator
+fn cached_pattern(app: &mut Application, node_key: NodeKey, pattern: &str) -> Result<Rc<Pattern>, Error> {
+    let m = app.view[node_key].factory.get().ok_or_else(|| error!())?;
+    let cache: &mut PatternCache = get_storage(&mut app.mutators, m).ok_or_else(|| error!())?;
+
+    if let Some(dfa) = cache.get(pattern) {
+        return Ok(dfa.clone());
+    }
+
+    let dfa = Rc::new(compile_pattern(pattern)?);
+    cache.insert_ref(pattern, dfa.clone());
+    Ok(dfa)
+}
+
+/// Returns the masking character of the node, if any
+fn mask(app: &mut Application, node_key: NodeKey, attributes: TextAttributes) -> Result<Option<char>, Error> {
+    Ok(match attributes.mask {
+        Some(mask) => app.attr::<Option<ArcStr>>(node_key, mask)?.and_then(|m| m.chars().next()),
+        None => None,
+    })
+}
+
+pub(crate) fn is_editable(app: &mut Application, node_key: NodeKey, attributes: TextAttributes) -> Result<bool, Error> {
+    app.attr(node_key, attributes.editable)
+}
+
+/// Returns the text to display, and whether or not it's the placeholder
+///
+/// If the node has a `mask`, every character of the text is replaced with it.
+pub(crate) fn display_text(app: &mut Application, node_key: NodeKey, attributes: TextAttributes) -> Result<(ArcStr, bool), Error> {
+    let text: ArcStr = app.attr(node_key, attributes.text)?;
+    let placeholder: Option<ArcStr> = app.attr(node_key, attributes.placeholder)?;
+    let mask = mask(app, node_key, attributes)?;
+
+    Ok(match (text.len(), placeholder, mask) {
+        (0, Some(placeholder), _) => (placeholder, true),
+        (_, _, Some(mask)) => (mask_text(&text, mask).into(), false),
+        _ => (text, false),
+    })
+}
+
+/// Returns the style to render a textual node with
+///
+/// If the text of the node isn't valid, this is the `warn-inert` or
+/// `warn-focus` style of the theme and the node gets its background
+/// until the text is valid again.
+pub(crate) fn input_style(
+    app: &mut Application,
+    node_key: NodeKey,
+    attributes: TextAttributes,
+) -> Result<Style, Error> {
+    let text: ArcStr = app.attr(node_key, attributes.text)?;
+    let (_, validity) = validity(app, node_key, attributes, &text)?;
+
+    let warn_style = match (validity, Some(node_key) == app.get_explicit_focus()) {
+        (Validity::Valid, _) => None,
+        (_, true) => Some("warn-focus"),
+        (_, false) => Some("warn-inert"),
+    };
+
+    Ok(match warn_style {
+        Some(name) => {
+            let style_index = app.theme.resolve(name).ok_or_else(|| error!("Missing {} style in theme", name))?;
+            let style = app.theme.get(style_index);
+            app.view[node_key].background = PixelSource::SolidColor(style.background);
+            style
+        },
+        None => {
+            // restore the background of the style of the node, if it has one
+            app.view[node_key].background = match app.view[node_key].style_override.get() {
+                Some(style_index) => PixelSource::SolidColor(app.theme.get(style_index.into()).background),
+                None => PixelSource::None,
+            };
+
+            app.get_inherited_style(node_key)?
+        },
+    })
+}
+
+/// Writes an edited text to the JSON state
+///
+/// Complete numbers are stored as JSON numbers, unless that would
+/// change their text while it's being edited (e.g. `1.50` or `1e3`).
+fn write_text(app: &mut Application, path: &Path, input_type: InputType, text: String, normalize: bool) {
+    let number = match (input_type.is_numeric(), text.parse::<f64>()) {
+        (true, Ok(number)) if normalize || format!("{}", number) == text => Some(number),
+        _ => None,
+    };
+
+    match number {
+        Some(number) => app.state.set_number(path, number),
+        None => app.state.set_string(path, text.into()),
+    }
+}
+
+/// Replaces every character of `text` with `mask`
+///
+/// Character positions are preserved, so cursors
+/// are valid in both strings.
+pub fn mask_text(text: &str, mask: char) -> String {
+    text.chars().map(|_| mask).collect()
+}
+
+/// How a textual node splits its text into unbreakables
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineBreaks {
+    /// The whole text is a single unbreakable
+    Never,
+    /// Unbreakables end at each UAX #14 line break opportunity.
+    ///
+    /// If `soft_hyphens` is false, soft hyphens are not
+    /// considered as line break opportunities.
+    Uax14 {
+        soft_hyphens: bool,
+    },
+}
+
+/// Splits a string at each line break opportunity
+///
+/// Unbreakables keep their trailing whitespace, so that
+/// concatenating them gives back the original string.
+pub fn break_lines(text: &str, line_breaks: LineBreaks) -> impl Iterator<Item=&str> {
+    break_lines_with_mandatory(text, line_breaks).map(|(unbreakable, _)| unbreakable)
+}
+
+/// Like [`break_lines`], but also tells if each unbreakable ends
+/// with a mandatory break (e.g. a line feed), after which a new
+/// line must start.
+pub fn break_lines_with_mandatory(text: &str, line_breaks: LineBreaks) -> impl Iterator<Item=(&str, bool)> {
+    let mut start = 0;
 
-    let maybe_unb = match paragraph {
-        true => break_ws(text).nth(cursor.unbreakable),
-        false => Some(text),
+    let is_opportunity = move |&(end, _): &(usize, _)| match line_breaks {
+        LineBreaks::Never => end == text.len(),
+        LineBreaks::Uax14 { soft_hyphens } => soft_hyphens || !text[..end].ends_with(SOFT_HYPHEN),
     };
 
-    let base = text.as_ptr() as usize;
+    linebreaks(text).filter(is_opportunity).filter_map(move |(end, opportunity)| {
+        let unbreakable = &text[start..end];
+        start = end;
+        match unbreakable.is_empty() {
+            true => None,
+            false => Some((unbreakable, opportunity == BreakOpportunity::Mandatory)),
+        }
+    })
+}
+
+/// Converts a cursor to a byte offset in `text`
+pub fn cursor_to_str_index(text: &str, line_breaks: LineBreaks, cursor: Cursor) -> usize {
     let mut str_index = 0;
-    if let Some(unbreakable) = maybe_unb {
-        let ptr = unbreakable.as_ptr() as usize;
-        str_index = ptr - base;
 
-        unbreakable.chars().take(cursor.char_pos as _).for_each(|c| str_index += c.len_utf8());
+    for (i, unbreakable) in break_lines(text, line_breaks).enumerate() {
+        if i == cursor.unbreakable {
+            let chars = unbreakable.chars().take(cursor.char_pos);
+            return str_index + chars.map(char::len_utf8).sum::<usize>();
+        }
+
+        str_index += unbreakable.len();
+    }
+
+    str_index
+}
+
+/// Converts a byte offset in `text` to a cursor
+///
+/// When the offset is between two unbreakables, the cursor
+/// is placed at the start of the second one.
+pub fn str_index_to_cursor(text: &str, line_breaks: LineBreaks, str_index: usize) -> Cursor {
+    let mut cursor = Cursor {
+        unbreakable: 0,
+        char_pos: 0,
+    };
+
+    let mut start = 0;
+    for (i, unbreakable) in break_lines(text, line_breaks).enumerate() {
+        let end = start + unbreakable.len();
+
+        if str_index < end {
+            cursor.unbreakable = i;
+            cursor.char_pos = unbreakable[..(str_index - start)].chars().count();
+            break;
+        }
+
+        cursor.unbreakable = i;
+        cursor.char_pos = unbreakable.chars().count();
+        start = end;
     }
 
-    Ok((cursor, str_index))
+    cursor
+}
+
+#[test]
+fn line_breaks() {
+    let uax14 = LineBreaks::Uax14 { soft_hyphens: true };
+    let text = "see https://example.com/a-b \u{4F60}\u{597D} co\u{AD}op";
+
+    let unbreakables: crate::Vec<&str> = break_lines(text, uax14).collect();
+    assert_eq!(unbreakables.concat(), text);
+    assert!(unbreakables.contains(&"\u{4F60}"));
+    assert!(unbreakables.contains(&"co\u{AD}"));
+
+    let no_shy = LineBreaks::Uax14 { soft_hyphens: false };
+    assert!(break_lines(text, no_shy).any(|u| u == "co\u{AD}op"));
+    assert_eq!(break_lines(text, LineBreaks::Never).count(), 1);
+
+    let mandatory: crate::Vec<_> = break_lines_with_mandatory("one two\nthree", uax14).collect();
+    assert_eq!(mandatory, [("one ", false), ("two\n", true), ("three", true)]);
+
+    for str_index in (0..=text.len()).filter(|i| text.is_char_boundary(*i)) {
+        let cursor = str_index_to_cursor(text, uax14, str_index);
+        assert_eq!(cursor_to_str_index(text, uax14, cursor), str_index);
+    }
+}
+
+fn get_cursor(
+    text_cursors: &[Cursor],
+    line_breaks: LineBreaks,
+    text: &str,
+) -> Result<(Cursor, usize), Error> {
+    let cursor = match text_cursors.first() {
+        Some(cursor) => Ok(*cursor),
+        None => Err(error!("TextInsert but no cursor?")),
+    }?;
+
+    Ok((cursor, cursor_to_str_index(text, line_breaks, cursor)))
 }
 
 pub(crate) fn text_edit(
-    paragraph: bool,
+    line_breaks: LineBreaks,
     app: &mut Application,
     node_key: NodeKey,
     event: &UserInputEvent,
-    editable: usize,
-    font_file: usize,
+    attributes: TextAttributes,
     font_size: Pixels,
-    text: usize,
 ) -> Result<bool, Error> {
-    let editable: ArcStr = app.attr(node_key, editable)?;
-    if editable != "true" {
+    // read-only text can be copied too
+    if let UserInputEvent::TextCopy = event {
+        match mask(app, node_key, attributes)? {
+            Some(_) => log::warn!("Cannot copy masked text"),
+            None => app.clipboard = Some(app.attr(node_key, attributes.text)?),
+        }
+
+        return Ok(true);
+    }
+
+    let editable = is_editable(app, node_key, attributes)?;
+    if !editable {
         return Ok(false);
     }
 
-    let text_path = match app.attr_state_path(node_key, text)? {
+    let text_path = match app.attr_state_path(node_key, attributes.text)? {
         Err(_) => {
             log::error!("Cannot modify state during TextInsert: attribute isn't a state path");
             return Ok(true);
@@ -71,8 +444,10 @@ pub(crate) fn text_edit(
         Ok((attr_path, _)) => attr_path,
     };
 
-    let font_file: ArcStr = app.attr(node_key, font_file)?;
-    let text: ArcStr = app.attr(node_key, text)?;
+    let font_file: ArcStr = app.attr(node_key, attributes.font)?;
+    let text: ArcStr = app.attr(node_key, attributes.text)?;
+    let mask = mask(app, node_key, attributes)?;
+    let paragraph = line_breaks != LineBreaks::Never;
 
     let mut handled = false;
 
@@ -96,6 +471,12 @@ pub(crate) fn text_edit(
         let font = get_font(&mut app.mutators, &font_file).unwrap();
         let font_size = font_size.to_num();
 
+        // hit-testing must be done on the displayed string
+        let displayed: ArcStr = match mask {
+            Some(mask) => mask_text(&text, mask).into(),
+            None => text.clone(),
+        };
+
         let mut candidate = None;
         let mut best_distance = SignedPixels::MAX;
 
@@ -134,32 +515,35 @@ pub(crate) fn text_edit(
                 }
             }
 
-            return false;
+            false
         };
 
-        if paragraph {
-            let mut unbrk_iter = break_ws(&text);
-            let mut unbrk_index = 0;
+        // empty paragraphs have a single unbreakable, for their caret
+        if paragraph && !text.is_empty() {
+            let mut unbrk_iter = break_lines(&displayed, line_breaks).enumerate();
 
             for_each_child!(app.view, node_key, child, {
-                let unbreakable = unbrk_iter.next().unwrap();
+                let (unbrk_index, unbreakable) = unbrk_iter.next().unwrap();
 
                 if check(unbreakable, unbrk_index, child) {
                     break;
                 }
-
-                unbrk_index += 1;
             });
         } else {
-            check(&text, 0, node_key);
+            check(&displayed, 0, node_key);
         }
 
-        if let Some((unbrk_index, char_pos)) = candidate {
-            app.text_cursors.clear();
-            app.text_cursors.push(Cursor {
-                unbreakable: unbrk_index,
+        if let Some((unbreakable, char_pos)) = candidate {
+            let cursor = Cursor {
+                unbreakable,
                 char_pos,
-            });
+            };
+
+            // the end of an unbreakable is also the start of the next one
+            let str_index = cursor_to_str_index(&text, line_breaks, cursor);
+
+            app.text_cursors.clear();
+            app.text_cursors.push(str_index_to_cursor(&text, line_breaks, str_index));
 
             app.set_focused_node(node_key)?;
         }
@@ -173,37 +557,23 @@ pub(crate) fn text_edit(
     else if let UserInputEvent::TextInsert(addition) = event {
         // todo: multi-cursor support
 
-        let (
-            mut cursor,
-            insert_pos,
-        ) = get_cursor(&app.text_cursors, paragraph, &text)?;
-
-        let maybe_unb = match paragraph {
-            true => break_ws(addition).last(),
-            false => addition.len().checked_sub(1).map(|_| *addition),
-        };
-
-        if let Some(last_new_unb) = maybe_unb {
-            let last_new_unb_len = last_new_unb.chars().count();
+        let (_, insert_pos) = get_cursor(&app.text_cursors, line_breaks, &text)?;
 
+        if !addition.is_empty() {
             let mut string = String::from(text.as_str());
             string.insert_str(insert_pos, addition);
-            app.state.set_string(&text_path, string.into());
 
-            let num_new_unb = match paragraph {
-                true => break_ws(addition).count() - 1,
-                false => 0,
-            };
-
-            cursor.unbreakable += num_new_unb;
-            cursor.char_pos = match num_new_unb > 0 {
-                true => last_new_unb_len,
-                false => cursor.char_pos + last_new_unb_len,
-            };
+            match validity(app, node_key, attributes, &string)? {
+                (_, Validity::Invalid) => log::warn!("Rejected invalid input: {:?}", addition),
+                (input_type, _) => {
+                    let new_pos = insert_pos + addition.len();
+                    app.text_cursors[0] = str_index_to_cursor(&string, line_breaks, new_pos);
 
-            app.text_cursors[0] = cursor;
+                    write_text(app, &text_path, input_type, string, false);
 
-            app.reload_view();
+                    app.reload_view();
+                },
+            }
         }
 
         handled = true;
@@ -212,40 +582,12 @@ pub(crate) fn text_edit(
     else if let UserInputEvent::TextDelete(deletion) = event {
         // todo: multi-cursor support
 
-        // #[allow(unused_assignments)]
-        let (
-            mut cursor,
-            del_pos,
-        ) = get_cursor(&app.text_cursors, paragraph, &text)?;
-
-        let del_range;
-
-        if *deletion < 0 {
-            let new_cursor = del_pos.checked_sub(deletion.abs() as _).unwrap_or(0);
-            del_range = new_cursor..del_pos;
-
-            if let Some(substring) = text.get(..new_cursor) {
-                let maybe_unb = match paragraph {
-                    true => break_ws(substring).last(),
-                    false => substring.len().checked_sub(1).map(|_| substring),
-                };
-
-                if let Some(last_new_unb) = maybe_unb {
-                    cursor.char_pos = last_new_unb.chars().count();
-                    cursor.unbreakable = match paragraph {
-                        true => break_ws(substring).count() - 1,
-                        false => 0,
-                    };
-                } else {
-                    cursor.unbreakable = 0;
-                    cursor.char_pos = 0;
-                }
+        let (_, del_pos) = get_cursor(&app.text_cursors, line_breaks, &text)?;
 
-                app.text_cursors[0] = cursor;
-            }
-        } else {
-            del_range = del_pos..(del_pos + (*deletion as usize));
-        }
+        let del_range = match *deletion < 0 {
+            true => del_pos.saturating_sub(deletion.unsigned_abs())..del_pos,
+            false => del_pos..(del_pos + (*deletion as usize)),
+        };
 
         if text.get(del_range.clone()).is_none() {
             log::error!("Invalid deletion offset");
@@ -253,8 +595,12 @@ pub(crate) fn text_edit(
         }
 
         let mut string = String::from(text.as_str());
-        string.replace_range(del_range, "");
-        app.state.set_string(&text_path, string.into());
+        string.replace_range(del_range.clone(), "");
+
+        app.text_cursors[0] = str_index_to_cursor(&string, line_breaks, del_range.start);
+
+        let (input_type, _) = validity(app, node_key, attributes, &string)?;
+        write_text(app, &text_path, input_type, string, false);
 
         app.reload_view();
 
@@ -264,6 +610,14 @@ pub(crate) fn text_edit(
     else if let UserInputEvent::FocusLoss = event {
         app.text_cursors.clear();
 
+        // edition is over: store complete numbers as numbers
+        if let (input_type @ (InputType::Integer | InputType::Number), Validity::Valid) = validity(app, node_key, attributes, &text)? {
+            if !matches!(app.state.get(&text_path), Value::Number(_)) && !text.is_empty() {
+                write_text(app, &text_path, input_type, text.as_str().into(), true);
+                app.reload_view();
+            }
+        }
+
         // trigger buffer refresh
         app.call_resizer(node_key)?;
 
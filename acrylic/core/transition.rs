@@ -0,0 +1,376 @@
+//! Interpolation of positions, sizes & colors over time
+
+use super::app::Application;
+use super::node::NodeKey;
+use super::visual::{Position, Size, SignedPixels, Pixels};
+use super::rgb::RGBA8;
+use super::for_each_child;
+use crate::{Error, error};
+use core::time::Duration;
+
+/// Speed curve of a transition
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Easing {
+    Linear,
+    #[default]
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Self::Linear),
+            "ease" => Some(Self::Ease),
+            "ease-in" => Some(Self::EaseIn),
+            "ease-out" => Some(Self::EaseOut),
+            "ease-in-out" => Some(Self::EaseInOut),
+            _ => None,
+        }
+    }
+
+    /// Control points of the equivalent CSS cubic bezier curve
+    fn control_points(self) -> (f32, f32, f32, f32) {
+        match self {
+            Self::Linear => (0.0, 0.0, 1.0, 1.0),
+            Self::Ease => (0.25, 0.1, 0.25, 1.0),
+            Self::EaseIn => (0.42, 0.0, 1.0, 1.0),
+            Self::EaseOut => (0.0, 0.0, 0.58, 1.0),
+            Self::EaseInOut => (0.42, 0.0, 0.58, 1.0),
+        }
+    }
+
+    /// Maps a linear progress (from 0 to 1) to an eased one
+    pub fn apply(self, t: f32) -> f32 {
+        if self == Self::Linear || t <= 0.0 || t >= 1.0 {
+            return t.clamp(0.0, 1.0);
+        }
+
+        let (x1, y1, x2, y2) = self.control_points();
+        let bezier = |p1: f32, p2: f32, s: f32| {
+            let r = 1.0 - s;
+            3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+        };
+
+        // x(s) is monotonic: find s so that x(s) = t
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..20 {
+            let s = (low + high) / 2.0;
+            match bezier(x1, x2, s) < t {
+                true => low = s,
+                false => high = s,
+            }
+        }
+
+        bezier(y1, y2, (low + high) / 2.0)
+    }
+}
+
+/// Values which can be transitioned
+pub(crate) trait Interpolate: Copy + PartialEq {
+    fn interpolate(from: Self, to: Self, t: f32) -> Self;
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+impl Interpolate for Position {
+    fn interpolate(from: Self, to: Self, t: f32) -> Self {
+        let x = lerp(from.x.to_num(), to.x.to_num(), t);
+        let y = lerp(from.y.to_num(), to.y.to_num(), t);
+        Self::new(SignedPixels::from_num(x), SignedPixels::from_num(y))
+    }
+}
+
+impl Interpolate for Size {
+    fn interpolate(from: Self, to: Self, t: f32) -> Self {
+        let w = lerp(from.w.to_num(), to.w.to_num(), t);
+        let h = lerp(from.h.to_num(), to.h.to_num(), t);
+        Self::new(Pixels::from_num(w.max(0.0)), Pixels::from_num(h.max(0.0)))
+    }
+}
+
+impl Interpolate for [RGBA8; 2] {
+    fn interpolate(from: Self, to: Self, t: f32) -> Self {
+        let channel = |a: u8, b: u8| lerp(a as f32, b as f32, t) as u8;
+        let color = |a: RGBA8, b: RGBA8| RGBA8::new(
+            channel(a.r, b.r),
+            channel(a.g, b.g),
+            channel(a.b, b.b),
+            channel(a.a, b.a),
+        );
+
+        [color(from[0], to[0]), color(from[1], to[1])]
+    }
+}
+
+/// A running interpolation
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Lerp<T> {
+    start: Duration,
+    from: T,
+    to: T,
+}
+
+/// Declarative transition of a node, see the `transition` attribute of containers
+///
+/// When the layout moves or resizes the node, or when its resizer
+/// changes its colors (see [`colors`]), the change is spread over
+/// `duration` instead of being applied at once. If a new target value
+/// appears while a transition runs, the transition restarts from
+/// the currently displayed value.
+///
+/// Transitions of a node are lost when the view is reloaded.
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub duration: Duration,
+    pub easing: Easing,
+    pub(crate) position: Option<Lerp<Position>>,
+    pub(crate) size: Option<Lerp<Size>>,
+    pub(crate) colors: Option<Lerp<[RGBA8; 2]>>,
+    /// Size of the node before the layout changed it
+    pub(crate) resized_from: Option<Size>,
+    /// Colors displayed by the node
+    shown_colors: Option<[RGBA8; 2]>,
+}
+
+impl Transition {
+    pub fn new(duration: Duration, easing: Easing) -> Self {
+        Self {
+            duration,
+            easing,
+            position: None,
+            size: None,
+            colors: None,
+            resized_from: None,
+            shown_colors: None,
+        }
+    }
+
+    /// Parses a duration (`200ms`, `0.2s`) optionally followed by an easing curve
+    ///
+    /// The default curve is `ease`; others are `linear`, `ease-in`, `ease-out` & `ease-in-out`.
+    pub fn parse(string: &str) -> Result<Self, Error> {
+        let mut parts = string.split_whitespace();
+
+        let duration = parts.next().ok_or_else(|| error!("Empty transition"))?;
+        let (number, unit) = match duration.strip_suffix("ms") {
+            Some(number) => (number, 0.001),
+            None => (duration.strip_suffix('s').unwrap_or(""), 1.0),
+        };
+
+        // rejects negative, infinite and overly large durations
+        let length = number.parse::<f64>().ok().and_then(|n| Duration::try_from_secs_f64(n * unit).ok());
+        let length = length.ok_or_else(|| error!("Invalid transition duration: {}", duration))?;
+
+        let easing = match parts.next() {
+            Some(name) => Easing::parse(name).ok_or_else(|| error!("Invalid easing curve: {}", name))?,
+            None => Easing::default(),
+        };
+
+        if let Some(extra) = parts.next() {
+            return Err(error!("Unexpected transition parameter: {}", extra));
+        }
+
+        Ok(Self::new(length, easing))
+    }
+
+    fn is_running(&self) -> bool {
+        self.position.is_some() || self.size.is_some() || self.colors.is_some()
+    }
+
+    /// Eased progress of an interpolation, from 0 to 1
+    fn progress(&self, age: Duration, start: Duration) -> f32 {
+        let elapsed = age.saturating_sub(start);
+        let t = match self.duration.is_zero() {
+            true => 1.0,
+            false => elapsed.as_secs_f32() / self.duration.as_secs_f32(),
+        };
+
+        self.easing.apply(t)
+    }
+
+    /// Currently displayed value; the interpolation is dropped once it's over
+    fn current<T: Interpolate>(&self, lerp: &mut Option<Lerp<T>>, age: Duration) -> Option<T> {
+        let Lerp { start, from, to } = (*lerp)?;
+        let t = self.progress(age, start);
+        if t >= 1.0 {
+            *lerp = None;
+        }
+
+        Some(T::interpolate(from, to, t))
+    }
+}
+
+/// Starts an interpolation from the displayed value, unless one already goes to `to`
+fn retarget<T: Interpolate>(lerp: &mut Option<Lerp<T>>, age: Duration, shown: T, to: T) {
+    match lerp {
+        Some(lerp) if lerp.to == to => (),
+        _ => *lerp = Some(Lerp { start: age, from: shown, to }),
+    }
+}
+
+/// Moves a node & its descendants
+fn shift(app: &mut Application, node_key: NodeKey, x: SignedPixels, y: SignedPixels) {
+    let position = &mut app.view[node_key].position;
+    position.x += x;
+    position.y += y;
+
+    for_each_child!(app.view, node_key, child, {
+        shift(app, child, x, y);
+    });
+}
+
+/// Moves & resizes a node to the displayed position & size
+///
+/// The previous rectangle of the node is repainted. Returns
+/// `true` if the resizer of the node was called.
+fn apply(app: &mut Application, node_key: NodeKey, position: Option<Position>, size: Option<Size>) -> Result<bool, Error> {
+    let node = &app.view[node_key];
+    let old_rect = (node.position, node.size);
+
+    if let Some(position) = position {
+        let (x, y) = (position.x - old_rect.0.x, position.y - old_rect.0.y);
+        shift(app, node_key, x, y);
+    }
+
+    let resized = match size {
+        Some(size) if size != old_rect.1 => {
+            app.view[node_key].size = size;
+            app.call_resizer(node_key)?;
+            true
+        },
+        _ => false,
+    };
+
+    if (app.view[node_key].position, app.view[node_key].size) != old_rect {
+        app.repaint(old_rect);
+    }
+
+    Ok(resized)
+}
+
+/// Called by the layout after it placed a node which has a transition
+///
+/// The layout moved the node (and its descendants) to its new
+/// position & gave it its new size; they are put back where they
+/// were displayed, so that [`step`] can then interpolate.
+pub(crate) fn relocated(app: &mut Application, node_key: NodeKey, old_position: Position) -> Result<(), Error> {
+    let age = app.age();
+    let node = &mut app.view[node_key];
+    let (position, size) = (node.position, node.size);
+
+    let transition = match node.transition.as_mut() {
+        Some(transition) => transition,
+        None => return Ok(()),
+    };
+
+    let was_running = transition.is_running();
+    let old_size = transition.resized_from.take().unwrap_or(size);
+
+    // the node wasn't displayed before: no transition
+    if old_size == Size::zero() {
+        transition.position = None;
+        transition.size = None;
+        return Ok(());
+    }
+
+    if old_position != position {
+        retarget(&mut transition.position, age, old_position, position);
+    }
+
+    if old_size != size {
+        retarget(&mut transition.size, age, old_size, size);
+    }
+
+    let shown_position = transition.current(&mut transition.position.clone(), age);
+    let shown_size = transition.current(&mut transition.size.clone(), age);
+
+    if !was_running && transition.is_running() {
+        app.animate(node_key, step);
+    }
+
+    apply(app, node_key, shown_position, shown_size).map(|_| ())
+}
+
+/// Returns the `[background, outline]` colors which a resizer should display
+///
+/// If the node has a transition and `target` differs from the
+/// displayed colors, intermediate colors are returned & the resizer
+/// will be called again until the transition is over.
+pub fn colors(app: &mut Application, node_key: NodeKey, target: [RGBA8; 2]) -> [RGBA8; 2] {
+    let age = app.age();
+    let transition = match app.view[node_key].transition.as_mut() {
+        Some(transition) => transition,
+        None => return target,
+    };
+
+    let was_running = transition.is_running();
+    let shown = *transition.shown_colors.get_or_insert(target);
+
+    if shown != target {
+        retarget(&mut transition.colors, age, shown, target);
+    }
+
+    let mut colors = transition.colors;
+    let shown = transition.current(&mut colors, age).unwrap_or(target);
+    transition.shown_colors = Some(shown);
+
+    if !was_running && transition.is_running() {
+        app.animate(node_key, step);
+    }
+
+    shown
+}
+
+/// Animation callback shared by all transitions
+fn step(app: &mut Application, node_key: NodeKey, _elapsed: Duration) -> Result<bool, Error> {
+    let age = app.age();
+    let transition = match app.view[node_key].transition.as_mut() {
+        Some(transition) => transition,
+        None => return Ok(false),
+    };
+
+    let mut position = transition.position;
+    let mut size = transition.size;
+    let shown_position = transition.current(&mut position, age);
+    let shown_size = transition.current(&mut size, age);
+    let colors_changing = transition.colors.is_some();
+
+    transition.position = position;
+    transition.size = size;
+
+    let resized = apply(app, node_key, shown_position, shown_size)?;
+
+    // the resizer calls `colors` which updates the interpolation
+    if colors_changing && !resized {
+        app.call_resizer(node_key)?;
+    }
+
+    Ok(match app.view[node_key].transition.as_ref() {
+        Some(transition) => transition.is_running(),
+        None => false,
+    })
+}
+
+#[test]
+fn parse_transition() {
+    let transition = Transition::parse("200ms ease-out").unwrap();
+    assert_eq!(transition.duration, Duration::from_millis(200));
+    assert_eq!(transition.easing, Easing::EaseOut);
+
+    let transition = Transition::parse("1.5s").unwrap();
+    assert_eq!(transition.duration, Duration::from_millis(1500));
+    assert_eq!(transition.easing, Easing::Ease);
+
+    assert!(Transition::parse("fast").is_err());
+    assert!(Transition::parse("200ms bouncy").is_err());
+    assert!(Transition::parse("-1s").is_err());
+    assert!(Transition::parse("1e300s").is_err());
+
+    let half = Easing::EaseInOut.apply(0.5);
+    assert!(half > 0.49 && half < 0.51);
+}
@@ -49,6 +49,19 @@ pub enum LayoutMode {
     Remaining(Ratio),
 }
 
+/// What happens to children which don't fit in their container
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Children are painted outside of the container
+    #[default]
+    Visible,
+    /// Children are cut at the edges of the container
+    Clip,
+    /// Children are cut at the edges of the container,
+    /// which has a scrollbar if they don't fit in it.
+    Scroll,
+}
+
 /// Utility to compute an aspect-ratio
 pub fn aspect_ratio(width: usize, height: usize) -> Ratio {
     if width != 0 && height != 0 {
@@ -80,6 +93,9 @@ const RESZ_MASK: u32 = 0x02_00_00_00;
 const HOVR_MASK: u32 = 0x01_00_00_00;
 const  GAP_MASK: u32 = 0x00_ff_ff_ff;
 
+/// Largest gap which a [`NodeConfig`] can store
+pub const MAX_CONTENT_GAP: Pixels = Pixels::from_bits(GAP_MASK);
+
 impl NodeConfig {
     #[inline(always)]
     pub fn set_content_axis(&mut self, content_axis: Axis) {
@@ -183,7 +199,7 @@ impl NodeConfig {
     #[inline(always)]
     pub fn set_content_gap(&mut self, content_gap: Pixels) {
         let content_gap = content_gap.to_bits();
-        assert_eq!(content_gap >> MODE_SHIFT, 0);
+        assert_eq!(content_gap & !GAP_MASK, 0);
         self.cfg &= !GAP_MASK;
         self.cfg |= content_gap;
     }
@@ -224,11 +240,14 @@ fn config() {
     let ratio = Ratio::from_num(3.5);
     let axis = Axis::Vertical;
     let layout_mode = LayoutMode::Remaining(ratio);
-    let cfg = NodeConfig::new_container(axis, px, layout_mode);
-
-    assert_eq!(cfg.content_axis(), axis);
-    assert_eq!(cfg.content_gap(), px);
-    assert_eq!(cfg.layout_mode(), layout_mode);
+    let mut cfg = NodeConfig::default();
+    cfg.set_content_axis(axis);
+    cfg.set_content_gap(px);
+    cfg.set_layout_mode(layout_mode);
+
+    assert_eq!(cfg.get_content_axis(), axis);
+    assert_eq!(cfg.get_content_gap(), px);
+    assert_eq!(cfg.get_layout_mode(), layout_mode);
 }
 
 /// General-purpose axis enumeration
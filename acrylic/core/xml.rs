@@ -1,13 +1,16 @@
 //! XML Layout Parsing
 
-use crate::{error, Error, String, Vec, vec, ArcStr, HashMap};
+use crate::{error, Error, String, Vec, vec, ArcStr, HashMap, Rc, format};
 use super::node::{Mutator, MutatorIndex, OptionalMutatorIndex};
 use super::visual::{Ratio, Pixels, SignedPixels};
-use core::{ops::Deref, str::from_utf8 as str_from_utf8, mem::ManuallyDrop};
+use super::interpolation::{Interpolation, Segment};
+use core::{ops::{Deref, Range}, str::from_utf8 as str_from_utf8, mem::ManuallyDrop};
 use xmlparser::{Tokenizer, Token, StrSpan};
-use oakwood::{NoCookie, index, tree};
+use oakwood::{NoCookie, index, tree, for_each_child};
+use crate::builtin::template::{NAME as TEMPLATE_NAME, PARAMS as TEMPLATE_PARAMS};
 
 index!(LineNumber, OptionalLineNumber);
+index!(ColumnNumber, OptionalColumnNumber);
 index!(FileIndex, OptionalFileIndex);
 
 tree!(XmlNodeTree, XmlNode, XmlNodeKey, XmlNodeIndex, OptionalXmlNodeIndex, NoCookie);
@@ -19,6 +22,11 @@ pub struct XmlTagParameters {
     /// (xml_name, type, optional_default_value)
     pub attr_set: &'static [(&'static str, AttributeValueType, Option<ManuallyDrop<ArcStr>>)],
     pub accepts_children: bool,
+    /// Index of the attribute which receives the text content of the tag, if any
+    ///
+    /// In text content, whitespace sequences are collapsed to a single
+    /// space; CDATA sections are kept as is (they're never interpolated).
+    pub text_content: Option<usize>,
 }
 
 /// An XML Node extracted from the layout file
@@ -26,71 +34,252 @@ pub struct XmlTagParameters {
 pub struct XmlNode {
     pub attributes: AttributeValueVec,
     pub factory: OptionalMutatorIndex,
+    /// Index of the source file, see [`Application::source_file`]
+    ///
+    /// [`Application::source_file`]: super::app::Application::source_file
     pub file: OptionalFileIndex,
     pub line: OptionalLineNumber,
+    pub column: OptionalColumnNumber,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 #[repr(transparent)]
 pub struct AttributeValueVec(Vec<AttributeValue>);
 
+type TagMap<'a> = HashMap<str, (&'a XmlTagParameters, MutatorIndex)>;
+
+/// Registry of the tags which can be used in XML layouts
+///
+/// Tags of [mutator libraries](super::node::MutatorLibrary) are only
+/// found with the prefix of their library.
+pub struct XmlTags<'a> {
+    mutators: &'a [Mutator],
+    /// Tags without prefix
+    tags: TagMap<'a>,
+    /// Prefix, mutator indices & tags of each library
+    libraries: Vec<(ArcStr, Range<usize>, TagMap<'a>)>,
+}
+
+impl<'a> XmlTags<'a> {
+    /// `libraries` are the prefixes & mutator indices of mutator libraries
+    pub fn new(mutators: &'a [Mutator], libraries: &[(ArcStr, Range<usize>)]) -> Self {
+        let map = |range: Range<usize>| {
+            let mut tags = TagMap::new();
+            for i in range {
+                if let Some(params) = &mutators[i].xml_params {
+                    tags.insert_ref(&params.tag_name.clone(), (params, i.into()));
+                }
+            }
+
+            tags
+        };
+
+        let mut tags = TagMap::new();
+        for (i, mutator) in mutators.iter().enumerate() {
+            let in_library = libraries.iter().any(|(_, range)| range.contains(&i));
+            if let (Some(params), false) = (&mutator.xml_params, in_library) {
+                tags.insert_ref(&params.tag_name.clone(), (params, i.into()));
+            }
+        }
+
+        let libraries = libraries.iter().map(|(prefix, range)| (prefix.clone(), range.clone(), map(range.clone()))).collect();
+
+        Self { mutators, tags, libraries }
+    }
+
+    /// Finds a tag; `prefix` is empty for tags which aren't from a library
+    pub fn get(&self, prefix: &str, tag_name: &str) -> Option<(&'a XmlTagParameters, MutatorIndex)> {
+        let tags = match prefix {
+            "" => &self.tags,
+            prefix => &self.libraries.iter().find(|(p, _, _)| &**p == prefix)?.2,
+        };
+
+        tags.get(tag_name).copied()
+    }
+
+    /// Prefix of the tag of a mutator
+    pub fn prefix(&self, mutator: MutatorIndex) -> &str {
+        let index = usize::from(mutator);
+        match self.libraries.iter().find(|(_, range, _)| range.contains(&index)) {
+            Some((prefix, _, _)) => prefix,
+            None => "",
+        }
+    }
+
+    /// Names of the tags which have a prefix
+    fn names(&self, prefix: &str) -> Vec<&'a str> {
+        let mutators = self.mutators;
+        (0..mutators.len())
+            .filter(|i| self.prefix((*i).into()) == prefix)
+            .filter_map(|i| mutators[i].xml_params.as_ref())
+            .map(|params| &*params.tag_name)
+            .collect()
+    }
+}
+
+/// A `<template>` defined in an XML layout
+///
+/// Once parsed, a template can be instantiated like a tag, in the
+/// same file or in files parsed later; see [`parse_xml_tree`].
+#[derive(Clone, Debug)]
+pub struct Template {
+    pub name: ArcStr,
+    pub params: Vec<ArcStr>,
+    /// The `<template>` node; its children are copied in instances
+    pub node: XmlNodeKey,
+}
+
+/// Templates which can be instantiated in XML layouts
+pub type Templates = Vec<Template>;
+
+/// Value of a template parameter, for one instance
+enum Argument {
+    Value(ArcStr),
+    Lookup {
+        namespace: ArcStr,
+        path: ArcStr,
+    },
+}
+
+/// Template instance being parsed
+struct Instance {
+    node: XmlNodeKey,
+    template: usize,
+    args: Vec<(ArcStr, Argument)>,
+    location: String,
+}
+
 /// Parses an XML Layout file and adds it as a new independant tree in `XmlNodeTree`.
+///
+/// `<template>` definitions are added to `templates`. Tags which
+/// instantiate a template are replaced by a copy of its children,
+/// where `param:` lookups are replaced by the attributes of the instance
+/// and where `<slot />` is replaced by the children of the instance.
+///
+/// Tags are found in `tags`; the prefix of a tag selects a
+/// [mutator library](super::node::MutatorLibrary).
+///
+/// Nodes record their location in the file, which is `file_name` in
+/// error messages and `file` in nodes.
 pub fn parse_xml_tree(
-    mutators_params: HashMap<str, (&XmlTagParameters, MutatorIndex)>,
-    ordered: &[Mutator],
+    tags: &XmlTags,
     tree: &mut XmlNodeTree,
+    templates: &mut Templates,
+    file: FileIndex,
+    file_name: &str,
     xml_bytes: &[u8],
 ) -> Result<XmlNodeKey, Error> {
     use Token::*;
 
-    let xml = str_from_utf8(xml_bytes).map_err(|e| error!("xml_bytes: {:?}", e))?;
-    let line = |span: StrSpan| xml[..span.start()].lines().count();
-    let unexpected = |thing, as_str, span| error!("Unexpected {}: {:?} (line {})", thing, as_str, line(span));
-    let unknown = |thing, as_str, span| error!("Unknown {}: {:?} (line {})", thing, as_str, line(span));
+    let xml = str_from_utf8(xml_bytes).map_err(|e| error!("{}: invalid UTF-8: {:?}", file_name, e))?;
+    let position = |span: StrSpan| {
+        let before = &xml[..span.start()];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+    };
+    let at = |span: StrSpan| {
+        let (line, column) = position(span);
+        format!("{}:{}:{}", file_name, line, column)
+    };
+    let unexpected = |thing, as_str, span| error!("{}: Unexpected {}: {:?}", at(span), thing, as_str);
+    let unknown = |thing, as_str, span, suggestion: String| error!("{}: Unknown {}: {:?}{}", at(span), thing, as_str, suggestion);
+
+    let ordered = tags.mutators;
+    let template_tag = tags.get("", "template").map(|(_, index)| index);
+    let slot_tag = tags.get("", "slot").map(|(_, index)| index);
+    let mut instances: Vec<Instance> = Vec::new();
+    let mut text_contents: Vec<(XmlNodeKey, String)> = Vec::new();
 
     let mut current = tree.create();
-    let mut xml_params = mutators_params.get("import").unwrap().0;
+    let mut xml_params = tags.get("", "import").unwrap().0;
     for token in Tokenizer::from(xml) {
-        let token = token.map_err(|e| error!("XML token error: {:?}", e))?;
+        let token = token.map_err(|e| error!("{}: XML token error: {}", file_name, e))?;
+        let in_instance = instances.last().map(|instance| instance.node) == Some(current);
 
         /**/ if let ElementStart { prefix, local, span } = token {
             let prefix = prefix.as_str();
             let local = local.as_str();
 
-            if prefix != "" {
-                return Err(unexpected("prefix", prefix, span));
+            if !prefix.is_empty() && !tags.libraries.iter().any(|(p, _, _)| &**p == prefix) {
+                let prefixes = tags.libraries.iter().map(|(p, _, _)| &**p);
+                return Err(unknown("tag prefix", prefix, span, suggest(prefix, prefixes)));
             }
 
-            let (new_xml_params, index) = match mutators_params.get(local) {
-                Some(tuple) => tuple,
-                None => return Err(unknown("XML tag", local, span)),
-            };
+            if tree[current].factory.get().is_some() && !xml_params.accepts_children {
+                return Err(unexpected("child", local, span));
+            }
 
+            let (line, column) = position(span);
             let new_node = tree.create();
-            tree[new_node].factory = Some(*index).into();
-            tree[new_node].line = Some(line(span).into()).into();
-            tree[new_node].attributes = AttributeValueVec::new(new_xml_params);
+            tree[new_node].file = Some(file).into();
+            tree[new_node].line = Some(line.into()).into();
+            tree[new_node].column = Some(column.into()).into();
             tree.append_children(new_node, current);
-
             current = new_node;
-            xml_params = new_xml_params;
+
+            if let Some((new_xml_params, index)) = tags.get(prefix, local) {
+                tree[new_node].factory = Some(index).into();
+                tree[new_node].attributes = AttributeValueVec::new(new_xml_params);
+                xml_params = new_xml_params;
+            } else if let (Some(template), "") = (templates.iter().position(|t| &*t.name == local), prefix) {
+                instances.push(Instance {
+                    node: new_node,
+                    template,
+                    args: Vec::new(),
+                    location: at(span),
+                });
+            } else {
+                let names = templates.iter().map(|t| &*t.name).filter(|_| prefix.is_empty());
+                let suggestion = suggest(local, tags.names(prefix).into_iter().chain(names));
+                let tag = match prefix {
+                    "" => String::from(local),
+                    prefix => format!("{}:{}", prefix, local),
+                };
+
+                return Err(unknown("XML tag", &*tag, span, suggestion));
+            }
         }
 
         else if let Attribute { prefix, local, value, span } = token {
-            let value = value.as_str();
+            let value = unescape(value.as_str()).map_err(|e| locate(e, &at(span)))?;
             let local = local.as_str();
 
+            if in_instance {
+                let instance = instances.last_mut().unwrap();
+                let params = &templates[instance.template].params;
+                if !params.iter().any(|param| &**param == local) {
+                    let suggestion = suggest(local, params.iter().map(|p| &**p));
+                    return Err(unknown("template parameter", local, span, suggestion));
+                }
+
+                let argument = match prefix.as_str() {
+                    "" => Argument::Value(value.clone().into()),
+                    prefix => Argument::Lookup {
+                        namespace: prefix.into(),
+                        path: value.into(),
+                    },
+                };
+
+                instance.args.push((local.into(), argument));
+                continue;
+            }
+
             // attr.0 is the xml name of the attribute
             let index = match xml_params.attr_set.iter().position(|attr| attr.0 == local) {
                 Some(index) => index,
-                None => return Err(unknown("attribute", local, span)),
+                None => {
+                    let suggestion = suggest(local, xml_params.attr_set.iter().map(|attr| attr.0));
+                    return Err(unknown("attribute", local, span, suggestion));
+                },
             };
 
             let value_type = xml_params.attr_set[index].1;
-            let value_rc = String::from(value);
+            let value_rc = value;
 
             tree[current].attributes.0[index] = match prefix.as_str() {
-                "" => AttributeValue::parse(&value_rc.into(), value_type)?,
+                // regular expressions are never interpolated
+                "" if local == "pattern" => AttributeValue::parse_literal(&value_rc.into(), value_type).map_err(|e| locate(e, &at(span)))?,
+                "" => AttributeValue::parse(&value_rc.into(), value_type).map_err(|e| locate(e, &at(span)))?,
                 prefix => AttributeValue::StateLookup { 
                     namespace: String::from(prefix).into(),
                     path: value_rc.into(),
@@ -100,23 +289,23 @@ pub fn parse_xml_tree(
         }
 
         else if let ElementEnd { end, span } = token {
-            let current_tag = xml_params.tag_name.deref();
+            let (current_prefix, current_tag) = match (in_instance, tree[current].factory.get()) {
+                (false, Some(index)) => (tags.prefix(index), xml_params.tag_name.clone()),
+                (true, _) => ("", templates[instances.last().unwrap().template].name.clone()),
+                (false, None) => ("", xml_params.tag_name.clone()),
+            };
 
             let pop = if let xmlparser::ElementEnd::Close(prefix, local) = end {
                 // "</tag>"
 
-                if !xml_params.accepts_children {
+                if !(in_instance || xml_params.accepts_children || xml_params.text_content.is_some()) {
                     return Err(unexpected("children", &local, local));
                 }
 
                 let prefix = prefix.as_str();
                 let local = local.as_str();
 
-                if prefix != "" {
-                    return Err(unexpected("prefix", prefix, span));
-                }
-
-                if local != current_tag {
+                if prefix != current_prefix || local != &*current_tag {
                     return Err(unexpected("close tag", local, span));
                 }
 
@@ -130,20 +319,57 @@ pub fn parse_xml_tree(
             };
 
             if pop {
-                if let Some(i) = tree[current].attributes.0.iter().position(|a| a == &AttributeValue::Unset) {
-                    let (attr_name, attr_type, _) = xml_params.attr_set[i];
-                    if required(attr_type) {
-                        return Err(error!("Missing XML attribute: {} (line {})", attr_name, line(span)));
+                let parent = tree.parent(current);
+
+                if in_instance {
+                    let instance = instances.pop().unwrap();
+                    let location = instance.location.clone();
+                    expand(tree, ordered, slot_tag, &templates[instance.template], instance)
+                        .map_err(|e| locate(e, &location))?;
+                } else {
+                    if text_contents.last().map(|(node, _)| *node) == Some(current) {
+                        let (_, text) = text_contents.pop().unwrap();
+                        let index = xml_params.text_content.unwrap();
+                        let (attr_name, value_type, default) = &xml_params.attr_set[index];
+
+                        let default = match default {
+                            Some(default) => AttributeValue::parse(default, *value_type)?,
+                            None => AttributeValue::Unset,
+                        };
+
+                        let attribute = &mut tree[current].attributes.0[index];
+                        if *attribute != default {
+                            return Err(error!("{}: Both a {} attribute and text content were given", at(span), attr_name));
+                        }
+
+                        *attribute = AttributeValue::parse(&text.trim().into(), *value_type).map_err(|e| locate(e, &at(span)))?;
+                    }
+
+                    if let Some(i) = tree[current].attributes.0.iter().position(|a| a == &AttributeValue::Unset) {
+                        let (attr_name, attr_type, _) = xml_params.attr_set[i];
+                        if required(attr_type) {
+                            return Err(error!("{}: Missing XML attribute: {}", at(span), attr_name));
+                        }
+                    }
+
+                    if template_tag.is_some() && tree[current].factory.get() == template_tag {
+                        let template = define_template(tree, current).map_err(|e| locate(e, &at(span)))?;
+                        if tags.get("", &template.name).is_some() {
+                            return Err(error!("{}: Template name is already a tag name: {}", at(span), template.name));
+                        }
+
+                        templates.retain(|t| t.name != template.name);
+                        templates.push(template);
                     }
                 }
 
-                if let Some(node) = tree.parent(current) {
+                if let Some(node) = parent {
                     current = node;
                     if let Some(index) = tree[current].factory.get() {
                         xml_params = ordered[usize::from(index)].xml_params.as_ref().unwrap();
                     }
                 } else {
-                    return Err(error!("malformed XML: {:?} (line {})", current_tag, line(span)));
+                    return Err(error!("{}: malformed XML: {:?}", at(span), current_tag));
                 }
             }
         }
@@ -155,18 +381,46 @@ pub fn parse_xml_tree(
         else if let Text { text } = token {
             let text_str = text.as_str().trim();
             if text_str != "" {
-                return Err(unexpected("text", text_str, text));
+                if in_instance || xml_params.text_content.is_none() {
+                    return Err(unexpected("text", text_str, text));
+                }
+
+                let unescaped = unescape(text.as_str()).map_err(|e| locate(e, &at(text)))?;
+                let mut collapsed = String::new();
+                for (i, word) in unescaped.split_whitespace().enumerate() {
+                    if i > 0 || unescaped.starts_with(char::is_whitespace) {
+                        collapsed.push(' ');
+                    }
+
+                    collapsed.push_str(word);
+                }
+
+                if unescaped.ends_with(char::is_whitespace) {
+                    collapsed.push(' ');
+                }
+
+                push_text(&mut text_contents, current, &collapsed);
             }
         }
 
+        else if let Cdata { text, span } = token {
+            if in_instance || xml_params.text_content.is_none() {
+                return Err(unexpected("CDATA section", text.as_str(), span));
+            }
+
+            // braces are escaped so that the section isn't interpolated
+            let escaped = text.as_str().replace('{', "{{").replace('}', "}}");
+            push_text(&mut text_contents, current, &escaped);
+        }
+
         else {
-            return Err(error!("Unknown token: {:?}", token));
+            return Err(error!("{}: Unknown token: {:?}", file_name, token));
         }
     }
 
     let node = match tree.detach_children(current) {
         Some(node) => Ok(node),
-        None => Err(error!("XML file appears to be empty; at least one node is required.")),
+        None => Err(error!("{}: XML file appears to be empty; at least one node is required.", file_name)),
     }?;
 
     tree.delete(current);
@@ -174,25 +428,226 @@ pub fn parse_xml_tree(
     Ok(node)
 }
 
+/// Prefixes the message of an error with a location, e.g. `file:line:column`
+pub(crate) fn locate(mut error: Error, location: &str) -> Error {
+    error.msg = Some(match error.msg.take() {
+        Some(msg) => format!("{}: {}", location, msg),
+        None => String::from(location),
+    });
+
+    error
+}
+
+/// Formats a suggestion for an unknown name, if one of the candidates is close enough
+fn suggest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    let max_distance = (name.chars().count() / 3).max(1);
+    let closest = candidates
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance);
+
+    match closest {
+        Some((_, candidate)) => format!(" (did you mean {:?}?)", candidate),
+        None => String::new(),
+    }
+}
+
+/// Edit distance between two strings, where swapping adjacent characters counts as one edit
+fn distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            let mut d = (rows[i - 1][j - 1] + cost).min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(rows[i - 2][j - 2] + 1);
+            }
+
+            rows[i][j] = d;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Appends text to the text content of `node`
+fn push_text(text_contents: &mut Vec<(XmlNodeKey, String)>, node: XmlNodeKey, text: &str) {
+    match text_contents.last_mut() {
+        Some((last, content)) if *last == node => content.push_str(text),
+        _ => text_contents.push((node, String::from(text))),
+    }
+}
+
+/// Decodes XML entities: `&lt;`, `&gt;`, `&amp;`, `&quot;`, `&apos;` & character references
+pub fn unescape(text: &str) -> Result<String, Error> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find('&') {
+        unescaped.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        let (entity, after) = rest.split_once(';').ok_or_else(|| error!("Unterminated entity in {:?}", text))?;
+        rest = after;
+
+        let character = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()).and_then(char::from_u32),
+            },
+        };
+
+        match character {
+            Some(character) => unescaped.push(character),
+            None => return Err(error!("Unknown entity: &{};", entity)),
+        }
+    }
+
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+fn define_template(tree: &XmlNodeTree, node: XmlNodeKey) -> Result<Template, Error> {
+    let attributes = &tree[node].attributes;
+
+    let name = match attributes.get(TEMPLATE_NAME) {
+        AttributeValue::Other(name) => name.clone(),
+        _ => return Err(error!("Template names cannot be JSON state lookups")),
+    };
+
+    let params = match attributes.get(TEMPLATE_PARAMS) {
+        AttributeValue::OptOther(Some(params)) => params.split_whitespace().map(ArcStr::from).collect(),
+        AttributeValue::Unset => Vec::new(),
+        _ => return Err(error!("Template parameters cannot be JSON state lookups")),
+    };
+
+    Ok(Template { name, params, node })
+}
+
+/// Replaces a template instance with a copy of the children of the template
+fn expand(
+    tree: &mut XmlNodeTree,
+    ordered: &[Mutator],
+    slot_tag: Option<MutatorIndex>,
+    template: &Template,
+    instance: Instance,
+) -> Result<(), Error> {
+    let mut slot_content = tree.detach_children(instance.node);
+    let mut prev = instance.node;
+
+    for_each_child!(tree, template.node, child, {
+        let copy = copy_subtree(tree, ordered, slot_tag, child, &instance.args, &mut slot_content)?;
+        if let Some(copy) = copy {
+            // the slot content can be multiple siblings
+            let next = tree.next_sibling(prev);
+            tree.insert_after(prev, copy);
+            prev = tree.prev_sibling(next);
+        }
+    });
+
+    if slot_content.is_some() {
+        return Err(error!("<{}> has no <slot> for its children", template.name));
+    }
+
+    tree.delete(instance.node);
+
+    Ok(())
+}
+
+/// Copies a node of a template & its descendants
+///
+/// The slot is replaced by `slot_content`, which can be multiple siblings.
+fn copy_subtree(
+    tree: &mut XmlNodeTree,
+    ordered: &[Mutator],
+    slot_tag: Option<MutatorIndex>,
+    node: XmlNodeKey,
+    args: &[(ArcStr, Argument)],
+    slot_content: &mut Option<XmlNodeKey>,
+) -> Result<Option<XmlNodeKey>, Error> {
+    if slot_tag.is_some() && tree[node].factory.get() == slot_tag {
+        return Ok(slot_content.take());
+    }
+
+    let mut copy = tree[node].clone();
+    let attr_set = match copy.factory.get() {
+        Some(index) => ordered[usize::from(index)].xml_params.as_ref().unwrap().attr_set,
+        None => &[],
+    };
+
+    for (i, attribute) in copy.attributes.0.iter_mut().enumerate() {
+        let (param, value_type) = match attribute {
+            AttributeValue::StateLookup { namespace, path, value_type } if &**namespace == "param" => (path.clone(), *value_type),
+            _ => continue,
+        };
+
+        *attribute = match args.iter().find(|(name, _)| *name == param) {
+            Some((_, Argument::Value(value))) if attr_set[i].0 == "pattern" => AttributeValue::parse_literal(value, value_type)?,
+            Some((_, Argument::Value(value))) => AttributeValue::parse(value, value_type)?,
+            Some((_, Argument::Lookup { namespace, path })) => AttributeValue::StateLookup {
+                namespace: namespace.clone(),
+                path: path.clone(),
+                value_type,
+            },
+            None => match &attr_set[i].2 {
+                Some(default) => AttributeValue::parse(default, value_type)?,
+                None if required(value_type) => return Err(error!("Missing template parameter: {}", param)),
+                None => AttributeValue::Unset,
+            },
+        };
+    }
+
+    let new_node = tree.create();
+    tree[new_node] = copy;
+
+    for_each_child!(tree, node, child, {
+        if let Some(child_copy) = copy_subtree(tree, ordered, slot_tag, child, args, slot_content)? {
+            tree.append_children(child_copy, new_node);
+        }
+    });
+
+    Ok(Some(new_node))
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum AttributeValueType {
     SignedPixels = 0,
     Pixels,
     Ratio,
+    Number,
+    Boolean,
     Other,
     OptSignedPixels,
     OptPixels,
     OptRatio,
+    OptNumber,
+    OptBoolean,
     OptOther,
 }
 
 const fn required(t: AttributeValueType) -> bool {
-    (t as u8) < 4
+    (t as u8) < 6
 }
 
 /// A Parsed XML Attribute value
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AttributeValue {
     OptSignedPixels(Option<SignedPixels>),
     SignedPixels(SignedPixels),
@@ -200,6 +655,10 @@ pub enum AttributeValue {
     Pixels(Pixels),
     OptRatio(Option<Ratio>),
     Ratio(Ratio),
+    OptNumber(Option<f64>),
+    Number(f64),
+    OptBoolean(Option<bool>),
+    Boolean(bool),
     OptOther(Option<ArcStr>),
     Other(ArcStr),
     StateLookup { 
@@ -207,12 +666,38 @@ pub enum AttributeValue {
         path: ArcStr,
         value_type: AttributeValueType,
     },
+    /// Text containing JSON state lookups, see [`Interpolation`]
+    Interpolation {
+        interpolation: Rc<Interpolation>,
+        value_type: AttributeValueType,
+    },
     Unset,
 }
 
+// Numbers are always finite (see `parse_literal`; JSON has no NaN)
+impl Eq for AttributeValue {}
+
 impl AttributeValue {
     /// Tries to parse an XML Value as some value type
+    ///
+    /// Values containing lookups between braces are interpolations: `text="Hello {root:user.name}"`.
     pub fn parse(xml_value: &ArcStr, attr_type: AttributeValueType) -> Result<Self, Error> {
+        if !xml_value.contains(['{', '}']) {
+            return Self::parse_literal(xml_value, attr_type);
+        }
+
+        let interpolation = Interpolation::parse(xml_value)?;
+        match interpolation.text() {
+            Some(text) => Self::parse_literal(&text, attr_type),
+            None => Ok(Self::Interpolation {
+                interpolation: Rc::new(interpolation),
+                value_type: attr_type,
+            }),
+        }
+    }
+
+    /// Tries to parse a string as some value type, without interpolation
+    pub fn parse_literal(xml_value: &ArcStr, attr_type: AttributeValueType) -> Result<Self, Error> {
         use AttributeValueType::*;
 
         macro_rules! parse_attr {
@@ -230,22 +715,59 @@ impl AttributeValue {
             };
         }
 
-        match attr_type {
+        let value = match attr_type {
             OptSignedPixels => parse_attr!(xml_value, OptSignedPixels, "a signed number of pixels", false),
             SignedPixels => parse_attr!(xml_value, SignedPixels, "a signed number of pixels", true),
             OptPixels => parse_attr!(xml_value, OptPixels, "an unsigned number of pixels", false),
             Pixels => parse_attr!(xml_value, Pixels, "an unsigned number of pixels", true),
             OptRatio => parse_attr!(xml_value, OptRatio, "a ratio", false),
             Ratio => parse_attr!(xml_value, Ratio, "a ratio", true),
+            OptNumber => parse_attr!(xml_value, OptNumber, "a number", false),
+            Number => parse_attr!(xml_value, Number, "a number", true),
+            OptBoolean => parse_attr!(xml_value, OptBoolean, "a boolean", false),
+            Boolean => parse_attr!(xml_value, Boolean, "a boolean", true),
             OptOther => Ok(Self::OptOther(Some(xml_value.clone()))),
             Other => Ok(Self::Other(xml_value.clone())),
+        }?;
+
+        match value {
+            Self::Number(n) | Self::OptNumber(Some(n)) if !n.is_finite() => {
+                Err(error!("Couldn't parse {} as a number: not finite", xml_value))
+            },
+            value => Ok(value),
         }
     }
+
+    /// Tries to convert a JSON number to some numeric value type
+    pub fn from_number(number: f64, attr_type: AttributeValueType) -> Result<Self, Error> {
+        use AttributeValueType as T;
+
+        macro_rules! convert {
+            ($fixed:ty) => {
+                match <$fixed>::checked_from_num(number) {
+                    Some(fixed) => Ok(fixed),
+                    None => Err(error!("Couldn't convert {} to {:?}: out of range", number, attr_type)),
+                }
+            };
+        }
+
+        Ok(match attr_type {
+            T::OptSignedPixels => Self::OptSignedPixels(Some(convert!(SignedPixels)?)),
+            T::SignedPixels => Self::SignedPixels(convert!(SignedPixels)?),
+            T::OptPixels => Self::OptPixels(Some(convert!(Pixels)?)),
+            T::Pixels => Self::Pixels(convert!(Pixels)?),
+            T::OptRatio => Self::OptRatio(Some(convert!(Ratio)?)),
+            T::Ratio => Self::Ratio(convert!(Ratio)?),
+            T::OptNumber => Self::OptNumber(Some(number)),
+            T::Number => Self::Number(number),
+            _ => return Err(error!("Couldn't convert {} to {:?}", number, attr_type)),
+        })
+    }
 }
 
 impl AttributeValueVec {
     pub(crate) fn new_import(layout_asset: ArcStr) -> Self {
-        Self(vec![ AttributeValue::Other(layout_asset) ])
+        Self(vec![ AttributeValue::Other(layout_asset), AttributeValue::Unset ])
     }
 
     pub fn new(params: &XmlTagParameters) -> Self {
@@ -264,6 +786,24 @@ impl AttributeValueVec {
     pub fn get(&self, index: usize) -> &AttributeValue {
         self.0.get(index).expect("Invalid Attribute Definition")
     }
+
+    /// Whether some attributes look values up in a namespace
+    pub fn uses_namespace(&self, ns_name: &str) -> bool {
+        let is_lookup = |segment: &Segment| match segment {
+            Segment::Lookup { namespace, .. } => &**namespace == ns_name,
+            Segment::Text(_) => false,
+        };
+
+        self.0.iter().any(|value| match value {
+            AttributeValue::StateLookup { namespace, .. } => &**namespace == ns_name,
+            AttributeValue::Interpolation { interpolation, .. } => interpolation.segments.iter().any(is_lookup),
+            _ => false,
+        })
+    }
+
+    pub fn set(&mut self, index: usize, value: AttributeValue) {
+        *self.0.get_mut(index).expect("Invalid Attribute Definition") = value;
+    }
 }
 
 macro_rules! impl_try_from {
@@ -298,9 +838,149 @@ macro_rules! impl_try_from_opt {
 impl_try_from_opt!(Option<SignedPixels>, OptSignedPixels);
 impl_try_from_opt!(Option<Pixels>, OptPixels);
 impl_try_from_opt!(Option<Ratio>, OptRatio);
+impl_try_from_opt!(Option<f64>, OptNumber);
+impl_try_from_opt!(Option<bool>, OptBoolean);
 impl_try_from_opt!(Option<ArcStr>, OptOther);
 
 impl_try_from!(SignedPixels, SignedPixels);
 impl_try_from!(Pixels, Pixels);
 impl_try_from!(Ratio, Ratio);
+impl_try_from!(f64, Number);
+impl_try_from!(bool, Boolean);
 impl_try_from!(ArcStr, Other);
+
+/// `<import>`, some other mutators, then the containers
+#[cfg(test)]
+fn test_mutators(mutators: &[Mutator]) -> Vec<Mutator> {
+    use crate::builtin::{import::IMPORT_MUTATOR, container::CONTAINERS};
+
+    let mut all = vec![IMPORT_MUTATOR];
+    all.extend_from_slice(mutators);
+    all.extend_from_slice(&CONTAINERS);
+    all
+}
+
+#[cfg(test)]
+fn parse_test_xml(xml_tags: &XmlTags, tree: &mut XmlNodeTree, xml: &[u8]) -> Result<XmlNodeKey, Error> {
+    parse_xml_tree(xml_tags, tree, &mut Templates::new(), 0.into(), "main.xml", xml)
+}
+
+#[test]
+fn templates() {
+    use crate::builtin::{inflate::INFLATE_MUTATOR, template::{TEMPLATE_MUTATOR, SLOT_MUTATOR}};
+
+    let mutators = test_mutators(&[INFLATE_MUTATOR, TEMPLATE_MUTATOR, SLOT_MUTATOR]);
+    let xml_tags = XmlTags::new(&mutators, &[]);
+
+    let xml = br#"
+        <v-wrap>
+            <template name="card" params="size">
+                <h-fixed param:length="size"><slot /></h-fixed>
+            </template>
+            <card size="40"><inflate /><inflate /></card>
+            <card root:size="sizes.0" />
+        </v-wrap>
+    "#;
+
+    let mut tree = XmlNodeTree::new();
+    let mut templates = Templates::new();
+    let root = parse_xml_tree(&xml_tags, &mut tree, &mut templates, 0.into(), "main.xml", xml).unwrap();
+    assert_eq!(templates.len(), 1);
+
+    let template = tree.first_child(root).unwrap();
+    let first = tree.next_sibling(template);
+    let second = tree.next_sibling(first);
+    assert_eq!(tree.last_child(root), Some(second));
+
+    let h_fixed = &mutators[usize::from(tree[first].factory.get().unwrap())];
+    let attr_set = h_fixed.xml_params.as_ref().unwrap().attr_set;
+    let length = attr_set.iter().position(|(name, _, _)| *name == "length").unwrap();
+    assert_eq!(tree[first].attributes.get(length), &AttributeValue::Pixels(Pixels::from_num(40)));
+    assert!(matches!(tree[second].attributes.get(length), AttributeValue::StateLookup { .. }));
+
+    let mut children = 0;
+    for_each_child!(tree, first, _child, {
+        children += 1;
+    });
+
+    assert_eq!(children, 2);
+    assert_eq!(tree.first_child(second), None);
+}
+
+#[test]
+fn text_content() {
+    use crate::builtin::label::LABEL_MUTATOR;
+
+    let mutators = test_mutators(&[LABEL_MUTATOR]);
+    let xml_tags = XmlTags::new(&mutators, &[]);
+
+    let xml = br#"
+        <v-wrap>
+            <label>
+                Fish &amp; <![CDATA[<Chips> {root:a}]]>
+                &#x2014; today
+            </label>
+        </v-wrap>
+    "#;
+
+    let mut tree = XmlNodeTree::new();
+    let root = parse_test_xml(&xml_tags, &mut tree, xml).unwrap();
+    let label = tree.first_child(root).unwrap();
+    assert_eq!(tree[label].attributes.get(0), &AttributeValue::Other("Fish & <Chips> {root:a} \u{2014} today".into()));
+
+    let both = br#"<v-wrap><label text="Fish">Chips</label></v-wrap>"#;
+    assert!(parse_test_xml(&xml_tags, &mut tree, both).is_err());
+
+    let child = br#"<v-wrap><label><label>Fish</label></label></v-wrap>"#;
+    assert!(parse_test_xml(&xml_tags, &mut tree, child).is_err());
+
+    let pattern = br#"<v-wrap><label text="Zip" pattern="[0-9]{5}" /></v-wrap>"#;
+    let root = parse_test_xml(&xml_tags, &mut tree, pattern).unwrap();
+    let label = tree.first_child(root).unwrap();
+    assert_eq!(tree[label].attributes.get(10), &AttributeValue::OptOther(Some("[0-9]{5}".into())));
+}
+
+#[test]
+fn diagnostics() {
+    use crate::builtin::label::LABEL_MUTATOR;
+
+    let mutators = test_mutators(&[LABEL_MUTATOR]);
+    let xml_tags = XmlTags::new(&mutators, &[]);
+    let parse = |xml: &[u8]| parse_test_xml(&xml_tags, &mut XmlNodeTree::new(), xml);
+
+    let error = parse(b"<v-wrap>\n    <lable text=\"Hi\" />\n</v-wrap>").unwrap_err();
+    assert_eq!(error.msg.as_deref(), Some(r#"main.xml:2:5: Unknown XML tag: "lable" (did you mean "label"?)"#));
+
+    let error = parse(b"<v-wrap>\n    <label txt=\"Hi\" />\n</v-wrap>").unwrap_err();
+    assert_eq!(error.msg.as_deref(), Some(r#"main.xml:2:12: Unknown attribute: "txt" (did you mean "text"?)"#));
+
+    let error = parse(b"<v-wrap gap=\"x\"></v-wrap>").unwrap_err();
+    assert!(error.msg.unwrap().starts_with("main.xml:1:"));
+}
+
+#[test]
+fn libraries() {
+    use crate::builtin::label::LABEL_MUTATOR;
+
+    let mut mutators = test_mutators(&[]);
+    let library = mutators.len()..mutators.len() + 1;
+    mutators.push(LABEL_MUTATOR);
+
+    let xml_tags = XmlTags::new(&mutators, &[("ui".into(), library.clone())]);
+    let parse = |xml: &[u8]| parse_test_xml(&xml_tags, &mut XmlNodeTree::new(), xml);
+
+    assert!(parse(br#"<v-wrap><ui:label text="Hi"></ui:label></v-wrap>"#).is_ok());
+    assert_eq!(xml_tags.get("ui", "label").map(|(_, index)| index), Some(library.start.into()));
+    assert!(xml_tags.get("", "label").is_none());
+
+    let error = parse(br#"<v-wrap><label text="Hi" /></v-wrap>"#).unwrap_err();
+    assert_eq!(error.msg.as_deref(), Some(r#"main.xml:1:9: Unknown XML tag: "label""#));
+
+    let error = parse(br#"<v-wrap><uu:label text="Hi" /></v-wrap>"#).unwrap_err();
+    assert_eq!(error.msg.as_deref(), Some(r#"main.xml:1:9: Unknown tag prefix: "uu" (did you mean "ui"?)"#));
+
+    let error = parse(br#"<v-wrap><ui:labl text="Hi" /></v-wrap>"#).unwrap_err();
+    assert_eq!(error.msg.as_deref(), Some(r#"main.xml:1:9: Unknown XML tag: "ui:labl" (did you mean "label"?)"#));
+
+    assert!(parse(br#"<v-wrap><ui:label text="Hi"></label></v-wrap>"#).is_err());
+}